@@ -0,0 +1,43 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Generates build information, that is exposed by `martos::version()`.
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .filter(|feature| feature != "default")
+        .collect();
+    features.sort();
+
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(
+        Path::new(&out_dir).join("version_info.rs"),
+        format!(
+            "const GIT_HASH: &str = {:?};\nconst BUILD_FEATURES: &[&str] = &{:?};\nconst TARGET: &str = {:?};\n",
+            git_hash, features, target
+        ),
+    )
+    .unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Martos C API.
+ *
+ * Declarations of functions exported by Martos static libraries built with the
+ * "c-library" feature. The header is kept in sync with src/c_api.rs by
+ * tests/c_header_tests.rs: every exported function must be declared here.
+ */
+#ifndef MARTOS_H
+#define MARTOS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Version of C ABI this header describes. Must be increased together with MARTOS_ABI_VERSION
+ * in src/version.rs whenever an exported signature or a type layout changes. */
+#define MARTOS_ABI_VERSION 8
+
+/* Number of ticks in timer. */
+typedef uint64_t TickType;
+
+/* Type of task identifier. */
+typedef size_t TaskIdType;
+
+/* Error codes returned by Martos functions. 0 means success. */
+enum MartosErrorCode {
+    /* System errors: -1..=-19. */
+    MARTOS_SYSTEM_REINIT_UNSUPPORTED = -1,
+    MARTOS_SYSTEM_ABI_MISMATCH = -2,
+    /* Timer errors: -100..=-119. */
+    MARTOS_TIMER_INVALID_INDEX = -100,
+    MARTOS_TIMER_BUSY = -101,
+    MARTOS_TIMER_RESERVED_BY_SYSTEM = -102,
+    MARTOS_TIMER_RUNNING = -103,
+    MARTOS_TIMER_UNSUPPORTED_RESOLUTION = -104,
+    /* Task inbox errors: -120..=-139. */
+    MARTOS_INBOX_NO_TASK = -120,
+    MARTOS_INBOX_NO_INBOX = -121,
+    MARTOS_INBOX_FULL = -122,
+    MARTOS_INBOX_TASK_FINISHED = -123,
+    /* Task state errors: -140..=-159. */
+    MARTOS_TASK_NOT_FOUND = -140,
+    MARTOS_TASK_INVALID_STATE = -141,
+    MARTOS_TASK_CURRENTLY_RUNNING = -142,
+    MARTOS_TASK_CLOCK_UNAVAILABLE = -143,
+    MARTOS_TASK_CANCELLED = -144,
+};
+
+/* Duration in seconds and microseconds. */
+typedef struct DurationFFI {
+    uint64_t secs;
+    uint32_t micros;
+} DurationFFI;
+
+/* Hardware timer. */
+typedef struct Timer {
+    uint8_t timer_index;
+    TickType tick_counter;
+} Timer;
+
+/* Result of get_timer: the timer is valid only if is_some is true. */
+typedef struct TimerOption {
+    bool is_some;
+    int32_t error_code;
+    Timer timer;
+} TimerOption;
+
+typedef void (*TaskSetupFunctionType)(void);
+typedef void (*TaskLoopFunctionType)(void);
+typedef bool (*TaskStopConditionFunctionType)(void);
+typedef void (*TaskExitHookType)(TaskIdType task_id, int32_t reason);
+typedef void (*TaskPreIterationHookType)(TaskIdType task_id);
+typedef void (*TaskPostIterationHookType)(TaskIdType task_id, bool ran_loop);
+
+/* System. */
+size_t martos_version_string(uint8_t *buf, size_t len);
+uint32_t martos_abi_version(void);
+int32_t martos_check_abi(uint32_t expected);
+/* Returns true if this call initialized Martos and false if it was already initialized. */
+bool init_system(void);
+int32_t shutdown_system(void);
+
+/* Checks that the library has the same ABI version as this header and initializes Martos.
+ * Returns 0 on success or MARTOS_SYSTEM_ABI_MISMATCH, in which case Martos is not initialized. */
+static inline int32_t martos_init_checked(void) {
+    int32_t result = martos_check_abi(MARTOS_ABI_VERSION);
+    if (result == 0) {
+        init_system();
+    }
+    return result;
+}
+#define MARTOS_INIT() martos_init_checked()
+
+/* Timers. */
+void setup_timer(void);
+TimerOption get_timer(uint8_t timer_index);
+void start_timer(const Timer *timer);
+void set_reload_mode(const Timer *timer, bool auto_reload);
+void change_period_timer(const Timer *timer, DurationFFI period);
+void loop_timer(Timer *timer);
+DurationFFI get_time(const Timer *timer);
+bool stop_condition_timer(const Timer *timer);
+void release_timer(const Timer *timer);
+
+/* Tasks. */
+TaskIdType add_task(TaskSetupFunctionType setup_fn, TaskLoopFunctionType loop_fn, TaskStopConditionFunctionType stop_condition_fn);
+void start_task_manager(void);
+/* Returns microseconds until the next call is needed: 0 if work is pending,
+ * time until the first sleeping task wakes up, or UINT64_MAX if idle.
+ * Preemptive task manager can not be run this way, so with the "preemptive" feature
+ * it always returns MARTOS_ITERATION_UNSUPPORTED. */
+#define MARTOS_ITERATION_UNSUPPORTED (UINT64_MAX - 1)
+uint64_t run_one_iteration(void);
+
+/* Cooperative task manager only: not exported with the "preemptive" feature. */
+TaskIdType add_named_task(const char *name, TaskSetupFunctionType setup_fn, TaskLoopFunctionType loop_fn, TaskStopConditionFunctionType stop_condition_fn);
+bool set_exit_hook(TaskIdType task_id, TaskExitHookType exit_hook);
+void set_iteration_hooks(TaskPreIterationHookType pre_iteration_hook, TaskPostIterationHookType post_iteration_hook);
+TaskIdType add_task_with_inbox(TaskSetupFunctionType setup_fn, TaskLoopFunctionType loop_fn, TaskStopConditionFunctionType stop_condition_fn, size_t inbox_capacity);
+int32_t task_send(TaskIdType task_id, uint32_t tag, const uint8_t *payload);
+bool task_recv(uint32_t *tag_out, uint8_t *payload_out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MARTOS_H */
@@ -0,0 +1,97 @@
+/*
+ * FreeRTOS compatibility layer of Martos.
+ *
+ * Declarations of the FreeRTOS API subset exported by Martos static libraries built with the
+ * "freertos-compat" feature. Code written against FreeRTOS is built with Martos by replacing
+ * FreeRTOS includes (FreeRTOS.h, task.h, queue.h, semphr.h) with this header. The header is kept
+ * in sync with src/freertos_compat.rs by tests/c_header_tests.rs.
+ *
+ * With preemptive task manager task functions loop forever like in FreeRTOS.
+ * With cooperative task manager task function is called repeatedly as loop function of the task,
+ * so it should do one iteration and return, and waiting in queue and semaphore calls is not supported.
+ */
+#ifndef MARTOS_FREERTOS_H
+#define MARTOS_FREERTOS_H
+
+#include "martos.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Signed type of results. */
+typedef int32_t BaseType_t;
+/* Unsigned type of counts and priorities. */
+typedef uint32_t UBaseType_t;
+/* Number of ticks. A tick is one millisecond of the system clock. */
+typedef uint32_t TickType_t;
+/* Handle of a task. */
+typedef void *TaskHandle_t;
+/* Handle of a queue. */
+typedef struct MartosQueue *QueueHandle_t;
+/* Handle of a binary semaphore. */
+typedef QueueHandle_t SemaphoreHandle_t;
+/* Function of a task, that gets parameters passed to xTaskCreate. */
+typedef void (*TaskFunction_t)(void *);
+
+#define pdPASS 1
+#define pdFAIL 0
+#define pdTRUE 1
+#define pdFALSE 0
+#define errQUEUE_FULL 0
+#define errQUEUE_EMPTY 0
+#define errCOULD_NOT_ALLOCATE_REQUIRED_MEMORY (-1)
+/* Result of functions, that have no Martos equivalent. */
+#define errMARTOS_UNSUPPORTED (-32)
+
+#define configTICK_RATE_HZ 1000
+#define portTICK_PERIOD_MS (1000 / configTICK_RATE_HZ)
+#define portMAX_DELAY ((TickType_t)0xffffffffUL)
+#define pdMS_TO_TICKS(xTimeInMs) ((TickType_t)(((uint64_t)(xTimeInMs) * configTICK_RATE_HZ) / 1000U))
+#define tskIDLE_PRIORITY 0
+
+/* Creates task. Martos is initialized, if it is not yet. Stack depth is in pointer-sized words
+ * and is honored only by preemptive task manager; priorities above the maximum Martos priority are
+ * truncated, cooperative task manager ignores them. */
+BaseType_t xTaskCreate(TaskFunction_t pxTaskCode, const char *pcName, uint16_t usStackDepth, void *pvParameters, UBaseType_t uxPriority, TaskHandle_t *pxCreatedTask);
+/* Terminates the task or the current task for NULL. */
+void vTaskDelete(TaskHandle_t xTask);
+/* Delays the current task, other tasks are executed meanwhile. */
+void vTaskDelay(TickType_t xTicksToDelay);
+/* Returns number of ticks since Martos initialization. */
+TickType_t xTaskGetTickCount(void);
+/* Initializes Martos, if it is not yet, and starts task manager. */
+void vTaskStartScheduler(void);
+
+QueueHandle_t xQueueCreate(UBaseType_t uxQueueLength, UBaseType_t uxItemSize);
+void vQueueDelete(QueueHandle_t xQueue);
+BaseType_t xQueueSend(QueueHandle_t xQueue, const void *pvItemToQueue, TickType_t xTicksToWait);
+#define xQueueSendToBack xQueueSend
+BaseType_t xQueueSendToFront(QueueHandle_t xQueue, const void *pvItemToQueue, TickType_t xTicksToWait);
+/* Never switches tasks, so pxHigherPriorityTaskWoken is always set to pdFALSE. */
+BaseType_t xQueueSendFromISR(QueueHandle_t xQueue, const void *pvItemToQueue, BaseType_t *pxHigherPriorityTaskWoken);
+BaseType_t xQueueReceive(QueueHandle_t xQueue, void *pvBuffer, TickType_t xTicksToWait);
+UBaseType_t uxQueueMessagesWaiting(QueueHandle_t xQueue);
+
+/* Creates binary semaphore, that is initially taken. */
+SemaphoreHandle_t xSemaphoreCreateBinary(void);
+void vSemaphoreDelete(SemaphoreHandle_t xSemaphore);
+BaseType_t xSemaphoreTake(SemaphoreHandle_t xSemaphore, TickType_t xBlockTime);
+BaseType_t xSemaphoreGive(SemaphoreHandle_t xSemaphore);
+/* Never switches tasks, so pxHigherPriorityTaskWoken is always set to pdFALSE. */
+BaseType_t xSemaphoreGiveFromISR(SemaphoreHandle_t xSemaphore, BaseType_t *pxHigherPriorityTaskWoken);
+
+/* Sets hook, that gets name of unsupported function, when it is called for the first time
+ * after the hook is set. Hook may be NULL. */
+void martos_freertos_set_unsupported_hook(void (*hook)(const char *name));
+
+/* Task notifications are not supported. These functions return errMARTOS_UNSUPPORTED. */
+BaseType_t xTaskNotifyGive(TaskHandle_t xTaskToNotify);
+BaseType_t xTaskNotify(TaskHandle_t xTaskToNotify, uint32_t ulValue, int32_t eAction);
+BaseType_t xTaskNotifyWait(uint32_t ulBitsToClearOnEntry, uint32_t ulBitsToClearOnExit, uint32_t *pulNotificationValue, TickType_t xTicksToWait);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MARTOS_FREERTOS_H */
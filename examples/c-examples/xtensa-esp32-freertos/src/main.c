@@ -0,0 +1,38 @@
+#include "martos_freertos.h"
+
+/* Queue, that passes counter values from the producer to the consumer. */
+static QueueHandle_t xQueue;
+/* The last value received by the consumer. */
+static volatile uint32_t ulReceived = 0;
+
+static void vProducerTask(void *pvParameters) {
+    uint32_t ulValue = 0;
+
+    for (;;) {
+        ulValue++;
+        xQueueSend(xQueue, &ulValue, 0);
+        vTaskDelay(pdMS_TO_TICKS(100));
+    }
+}
+
+static void vConsumerTask(void *pvParameters) {
+    uint32_t ulValue;
+
+    for (;;) {
+        if (xQueueReceive(xQueue, &ulValue, portMAX_DELAY) == pdPASS) {
+            ulReceived = ulValue;
+        }
+    }
+}
+
+int main(void) {
+    xQueue = xQueueCreate(8, sizeof(uint32_t));
+    xTaskCreate(vProducerTask, "producer", 1024, NULL, tskIDLE_PRIORITY + 2, NULL);
+    xTaskCreate(vConsumerTask, "consumer", 1024, NULL, tskIDLE_PRIORITY + 1, NULL);
+    vTaskStartScheduler();
+
+    /* (Should never be reached) */
+    for (;;) {
+    }
+    return 0;
+}
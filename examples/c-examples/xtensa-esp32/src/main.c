@@ -1,21 +1,20 @@
 #include <string.h>
 #include <stdbool.h>
 
+#include "martos.h"
+
 extern unsigned int _sbss, _ebss, _sidata, _sdata, _edata;
-extern void add_task(void (*setup_fn)(), void (*loop_fn)(), bool (*stop_condition_fn)());
-extern void start_task_manager();
-extern void init_system();
 
 int counter = 0;
 
-void setup_fn() {
+void setup_fn(void) {
 }
 
-void loop_fn() {
+void loop_fn(void) {
     counter++;
 }
 
-bool stop_condition_fn() {
+bool stop_condition_fn(void) {
     if (counter == 50) {
         return true;
     }
@@ -24,7 +23,10 @@ bool stop_condition_fn() {
 }
 
 int main( void ) {
-    init_system();
+    // Initialize Martos, checking that the library matches martos.h.
+    if (MARTOS_INIT() != 0) {
+        return 1;
+    }
     add_task(setup_fn, loop_fn, stop_condition_fn);
     start_task_manager();
 
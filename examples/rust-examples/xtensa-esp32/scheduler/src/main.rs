@@ -4,11 +4,11 @@
 use core::sync::atomic::{AtomicU32, Ordering};
 use esp_backtrace as _;
 use esp_hal::entry;
-use esp_hal::xtensa_lx_rt::xtensa_lx::timer::delay;
 use esp_println::println;
 use martos::{
     init_system,
     task_manager::{TaskManager, TaskManagerTrait},
+    timer::delay_ms,
 };
 
 /// Counter to work with in loop.
@@ -18,13 +18,13 @@ static COUNTER: AtomicU32 = AtomicU32::new(1);
 fn loop_fn_1() {
     let old = COUNTER.fetch_add(1, Ordering::Relaxed);
     println!("Loop 0; Counter = {}", old);
-    delay(10_000_000);
+    delay_ms(100);
 }
 
 fn loop_fn_2() {
     let old = COUNTER.fetch_add(1, Ordering::Relaxed);
     println!("Loop 1; Counter = {}", old);
-    delay(10_000_000);
+    delay_ms(100);
 }
 
 fn setup() {
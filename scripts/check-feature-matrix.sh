@@ -0,0 +1,74 @@
+#!/usr/bin/env bash
+# Checks documented feature combinations of Martos.
+# Valid combinations must build, invalid ones must fail with the message from src/config_check.rs.
+#
+# Host combinations are always checked. Additional targets can be passed in MARTOS_MATRIX_TARGETS,
+# e.g. MARTOS_MATRIX_TARGETS="riscv32imac-unknown-none-elf xtensa-esp32-none-elf mips64el-unknown-linux-gnuabi64".
+# Toolchains for these targets should be set up beforehand (see .github/workflows/rust.yml).
+set -u
+
+cd "$(dirname "$0")/.."
+
+failed=0
+
+# Checks that combination of features builds for the target.
+check_valid() {
+    local target=$1 features=$2
+    if cargo check --lib ${target:+--target "$target"} --no-default-features -F "$features" >/dev/null 2>&1; then
+        echo "ok       ${target:-host} [$features]"
+    else
+        echo "FAILED   ${target:-host} [$features] should build"
+        failed=1
+    fi
+}
+
+# Checks that combination of features is rejected with the expected message.
+check_invalid() {
+    local target=$1 features=$2 message=$3
+    local output
+    output=$(cargo check --lib ${target:+--target "$target"} --no-default-features -F "$features" 2>&1)
+    if [ $? -ne 0 ] && grep -qF "$message" <<<"$output"; then
+        echo "rejected ${target:-host} [$features]"
+    else
+        echo "FAILED   ${target:-host} [$features] should be rejected with: $message"
+        failed=1
+    fi
+}
+
+VALID_FEATURES=(
+    ""
+    "cooperative"
+    "preemptive"
+    "network"
+    "c-library"
+    "cooperative,network"
+    "preemptive,network"
+    "c-library,network"
+    "freertos-compat"
+    "preemptive,freertos-compat"
+)
+
+for features in "${VALID_FEATURES[@]}"; do
+    check_valid "" "$features"
+done
+check_valid "" "mips64_timer_tests"
+check_invalid "" "cooperative,preemptive" "can not be enabled together"
+
+for target in ${MARTOS_MATRIX_TARGETS:-}; do
+    case "$target" in
+    mips64*)
+        for features in "" "cooperative" "network" "c-library" "freertos-compat"; do
+            check_valid "$target" "$features"
+        done
+        check_invalid "$target" "preemptive" "not yet implemented for target mips64"
+        ;;
+    *)
+        for features in "${VALID_FEATURES[@]}"; do
+            check_valid "$target" "$features"
+        done
+        ;;
+    esac
+    check_invalid "$target" "cooperative,preemptive" "can not be enabled together"
+done
+
+exit $failed
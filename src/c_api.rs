@@ -1,4 +1,6 @@
-use crate::{task_manager, timer};
+use crate::error::{MartosError, SystemError};
+use crate::{task_manager, timer, version};
+use core::mem::ManuallyDrop;
 use core::time::Duration;
 use task_manager::{TaskManager, TaskManagerTrait};
 use timer::Timer;
@@ -16,13 +18,54 @@ pub struct DurationFFI {
 pub struct TimerOption {
     /// Indicator whether the timer exists.
     is_some: bool,
-    /// The timer itself.
-    timer: Timer,
+    /// Error code if the timer does not exist, 0 otherwise. See [crate::error] for code ranges.
+    error_code: i32,
+    /// The timer itself. It is owned by C code, so it is never dropped on Rust side.
+    timer: ManuallyDrop<Timer>,
 }
 
+/// Writes Martos version string to the buffer of the specified length as NUL-terminated string,
+/// truncating it if necessary. Returns length of the full version string without NUL, like snprintf.
+/// # Safety
+/// `buf` should point to writable memory of at least `len` bytes or be null if `len` is 0.
 #[no_mangle]
-pub extern "C" fn init_system() {
-    super::init_system();
+pub unsafe extern "C" fn martos_version_string(buf: *mut u8, len: usize) -> usize {
+    if buf.is_null() || len == 0 {
+        return version::write_version_string(&mut []);
+    }
+    version::write_version_string(core::slice::from_raw_parts_mut(buf, len))
+}
+
+/// Returns version of C ABI of the library.
+#[no_mangle]
+pub extern "C" fn martos_abi_version() -> u32 {
+    version::MARTOS_ABI_VERSION
+}
+
+/// Checks that the application is built against the same C ABI version as the library.
+/// Returns 0 if versions match or error code (see [crate::error]) otherwise.
+#[no_mangle]
+pub extern "C" fn martos_check_abi(expected: u32) -> i32 {
+    if expected == version::MARTOS_ABI_VERSION {
+        0
+    } else {
+        MartosError::from(SystemError::AbiMismatch).error_code()
+    }
+}
+
+/// Initializes Martos. Returns true if this call initialized Martos and false if it was already initialized.
+#[no_mangle]
+pub extern "C" fn init_system() -> bool {
+    super::init_system()
+}
+
+/// Releases all timers and removes all tasks. Returns 0 on success or error code (see [crate::error]).
+#[no_mangle]
+pub extern "C" fn shutdown_system() -> i32 {
+    match super::shutdown_system() {
+        Ok(()) => 0,
+        Err(error) => MartosError::from(error).error_code(),
+    }
 }
 
 #[no_mangle]
@@ -32,19 +75,17 @@ pub extern "C" fn setup_timer() {
 
 #[no_mangle]
 pub extern "C" fn get_timer(timer_index: u8) -> TimerOption {
-    if let Some(timer) = Timer::get_timer(timer_index) {
-        TimerOption {
+    match Timer::get_timer(timer_index) {
+        Ok(timer) => TimerOption {
             is_some: true,
-            timer,
-        }
-    } else {
-        TimerOption {
+            error_code: 0,
+            timer: ManuallyDrop::new(timer),
+        },
+        Err(error) => TimerOption {
             is_some: false,
-            timer: Timer {
-                timer_index: 0,
-                tick_counter: 0,
-            },
-        }
+            error_code: MartosError::from(error).error_code(),
+            timer: ManuallyDrop::new(Timer::unacquired()),
+        },
     }
 }
 
@@ -82,18 +123,156 @@ pub extern "C" fn stop_condition_timer(timer: &Timer) -> bool {
     Timer::stop_condition_timer(timer)
 }
 
+/// Releases the hardware timer. Timers owned by C code are not dropped, so they should be released explicitly.
 #[no_mangle]
 pub extern "C" fn release_timer(timer: &Timer) {
-    Timer::release_timer(timer)
+    timer.release_hardware_timer()
 }
 
+/// Adds task to task manager. Returns identifier of the task.
 #[no_mangle]
 pub extern "C" fn add_task(
     setup_fn: extern "C" fn() -> (),
     loop_fn: extern "C" fn() -> (),
     stop_condition_fn: extern "C" fn() -> bool,
+) -> task_manager::TaskIdType {
+    TaskManager::add_task_with_id(setup_fn, loop_fn, stop_condition_fn)
+}
+
+/// Adds task with the specified name. The name is a NUL-terminated string, that is copied
+/// and truncated to [task_manager::TASK_NAME_MAX_LEN] bytes. Returns identifier of the task.
+/// # Safety
+/// `name` should point to a NUL-terminated string or be null for unnamed task.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub unsafe extern "C" fn add_named_task(
+    name: *const core::ffi::c_char,
+    setup_fn: extern "C" fn() -> (),
+    loop_fn: extern "C" fn() -> (),
+    stop_condition_fn: extern "C" fn() -> bool,
+) -> task_manager::TaskIdType {
+    let name = if name.is_null() {
+        &[]
+    } else {
+        core::ffi::CStr::from_ptr(name).to_bytes()
+    };
+    TaskManager::add_task_with_name(
+        task_manager::cooperative::TaskName::from_bytes(name),
+        setup_fn,
+        loop_fn,
+        stop_condition_fn,
+    )
+}
+
+/// Sets exit hook of the task. The hook gets task identifier and exit reason code:
+/// 0 for stop condition, n > 0 if the task is restarted for the n-th time, -1 if parent of the task is completed,
+/// -2 if the task is terminated.
+/// Returns false if there is no task with such identifier.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub extern "C" fn set_exit_hook(
+    task_id: task_manager::TaskIdType,
+    exit_hook: extern "C" fn(task_manager::TaskIdType, i32),
+) -> bool {
+    TaskManager::set_exit_hook(task_id, exit_hook)
+}
+
+/// Sets hooks, that are called before and after every iteration of task manager.
+/// Post-iteration hook gets indicator whether loop function of the task was called. Hooks may be null.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub extern "C" fn set_iteration_hooks(
+    pre_iteration_hook: Option<extern "C" fn(task_manager::TaskIdType)>,
+    post_iteration_hook: Option<extern "C" fn(task_manager::TaskIdType, bool)>,
 ) {
-    TaskManager::add_task(setup_fn, loop_fn, stop_condition_fn)
+    TaskManager::set_iteration_hooks(pre_iteration_hook, post_iteration_hook)
+}
+
+/// Adds task with inbox of the specified capacity. Returns identifier of the task.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub extern "C" fn add_task_with_inbox(
+    setup_fn: extern "C" fn() -> (),
+    loop_fn: extern "C" fn() -> (),
+    stop_condition_fn: extern "C" fn() -> bool,
+    inbox_capacity: usize,
+) -> task_manager::TaskIdType {
+    TaskManager::add_task_with_inbox(setup_fn, loop_fn, stop_condition_fn, inbox_capacity)
+}
+
+/// Sends message with the specified tag and 8-byte payload to inbox of the task.
+/// Returns 0 on success or error code (see [crate::error]).
+/// # Safety
+/// `payload` should point to 8 readable bytes or be null for zero payload.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub unsafe extern "C" fn task_send(
+    task_id: task_manager::TaskIdType,
+    tag: u32,
+    payload: *const u8,
+) -> i32 {
+    let mut message = task_manager::InboxMessage {
+        tag,
+        payload: [0; 8],
+    };
+    if !payload.is_null() {
+        core::ptr::copy_nonoverlapping(
+            payload,
+            message.payload.as_mut_ptr(),
+            message.payload.len(),
+        );
+    }
+    match TaskManager::send_to(task_id, message) {
+        Ok(()) => 0,
+        Err(error) => MartosError::from(error).error_code(),
+    }
+}
+
+/// Receives the oldest message from inbox of the current task. Returns false if there are no messages.
+/// # Safety
+/// `tag_out` should point to writable u32 and `payload_out` to 8 writable bytes. Any of them may be null.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub unsafe extern "C" fn task_recv(tag_out: *mut u32, payload_out: *mut u8) -> bool {
+    match TaskManager::recv() {
+        Some(message) => {
+            if !tag_out.is_null() {
+                *tag_out = message.tag;
+            }
+            if !payload_out.is_null() {
+                core::ptr::copy_nonoverlapping(
+                    message.payload.as_ptr(),
+                    payload_out,
+                    message.payload.len(),
+                );
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Value returned by [run_one_iteration], when task manager can not be run by external environment.
+/// Sleep deadlines are shorter than `i64::MAX` microseconds, so it never means a deadline.
+pub const ITERATION_UNSUPPORTED: u64 = u64::MAX - 1;
+
+/// Runs one iteration of task manager and returns control. Returns time in microseconds
+/// until the next call is needed: 0 if work is pending, time until the first sleeping task wakes up,
+/// or u64::MAX if all tasks are completed or sleep until they are woken up.
+#[cfg(not(feature = "preemptive"))]
+#[no_mangle]
+pub extern "C" fn run_one_iteration() -> u64 {
+    TaskManager::run_one_iteration()
+        .next_deadline_us
+        .unwrap_or(u64::MAX)
+}
+
+/// Threads of preemptive task manager are switched only by the timer interrupt on their own stacks,
+/// so they can not be run by external environment. Always returns [ITERATION_UNSUPPORTED].
+#[cfg(feature = "preemptive")]
+#[no_mangle]
+pub extern "C" fn run_one_iteration() -> u64 {
+    ITERATION_UNSUPPORTED
 }
 
 #[no_mangle]
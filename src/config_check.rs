@@ -0,0 +1,10 @@
+//! Compile-time validation of feature and target combinations.
+//! Known-bad combinations are rejected here with a clear message instead of type errors deep in ports.
+
+#[cfg(all(feature = "cooperative", feature = "preemptive"))]
+compile_error!(
+    "features `cooperative` and `preemptive` select different task managers and can not be enabled together"
+);
+
+#[cfg(all(target_arch = "mips64", feature = "preemptive"))]
+compile_error!("feature `preemptive` is not yet implemented for target mips64");
@@ -0,0 +1,51 @@
+//! Dump of the system state for post-mortem analysis.
+//!
+//! The dump is line-oriented. Every section starts with a `[name]` line, fields are written
+//! as `key=value` pairs, and the whole dump is enclosed between `=== martos state ===` and
+//! `=== end ===` lines, so it can be parsed by scripts.
+
+use crate::task_manager::TaskManager;
+use crate::timer::{list_timers, TimerSlotState};
+use crate::version;
+use core::fmt::{self, Write};
+
+/// Writes the current system state to `writer`.
+pub fn dump_state(writer: &mut dyn Write) -> fmt::Result {
+    writeln!(writer, "=== martos state ===")?;
+    let version = version();
+    writeln!(
+        writer,
+        "version={} git={} target={}",
+        version.crate_version, version.git_hash, version.target
+    )?;
+
+    writeln!(writer, "[timers]")?;
+    for slot in list_timers() {
+        match slot.state {
+            TimerSlotState::Free => writeln!(writer, "timer index={} state=free", slot.index)?,
+            TimerSlotState::UserAcquired => {
+                writeln!(writer, "timer index={} state=user", slot.index)?
+            }
+            TimerSlotState::Reserved(owner) => writeln!(
+                writer,
+                "timer index={} state=reserved owner={}",
+                slot.index, owner
+            )?,
+        }
+    }
+
+    writeln!(writer, "[tasks]")?;
+    TaskManager::write_tasks(writer)?;
+
+    #[cfg(feature = "preemptive")]
+    {
+        writeln!(writer, "[preemption]")?;
+        write!(writer, "isr_ticks={}", TaskManager::isr_tick_count())?;
+        if let Some(uptime_us) = TaskManager::last_switch_uptime_us() {
+            write!(writer, " last_switch_us={}", uptime_us)?;
+        }
+        writeln!(writer, " stalls={}", TaskManager::stall_count())?;
+    }
+
+    writeln!(writer, "=== end ===")
+}
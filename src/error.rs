@@ -0,0 +1,116 @@
+//! Errors of Martos subsystems.
+//!
+//! Every subsystem error can be converted into [MartosError] and mapped to a stable error code,
+//! which is used by C API. Error codes are negative, zero means success.
+//! Ranges of error codes per subsystem:
+//! - `-1..=-19`: system errors ([SystemError]);
+//! - `-100..=-119`: timer errors ([TimerError]);
+//! - `-120..=-139`: task inbox errors ([InboxError]);
+//! - `-140..=-159`: task state errors ([TaskError]).
+
+use crate::task_manager::{InboxError, TaskError};
+use crate::timer::TimerError;
+use core::fmt;
+
+/// Errors of system initialization and shutdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemError {
+    /// Platform does not support shutdown and repeated initialization.
+    ReinitUnsupported,
+    /// Application is built against a different C ABI version of Martos.
+    AbiMismatch,
+}
+
+impl SystemError {
+    /// Returns stable error code of the error. System error codes are in range `-1..=-19`.
+    pub fn error_code(&self) -> i32 {
+        match self {
+            SystemError::ReinitUnsupported => -1,
+            SystemError::AbiMismatch => -2,
+        }
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemError::ReinitUnsupported => {
+                write!(f, "platform does not support re-initialization")
+            }
+            SystemError::AbiMismatch => write!(f, "C ABI version mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for SystemError {}
+
+/// Error of any Martos subsystem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MartosError {
+    /// System error.
+    System(SystemError),
+    /// Timer error.
+    Timer(TimerError),
+    /// Task inbox error.
+    Inbox(InboxError),
+    /// Task state error.
+    Task(TaskError),
+}
+
+impl MartosError {
+    /// Returns stable error code of the error.
+    pub fn error_code(&self) -> i32 {
+        match self {
+            MartosError::System(error) => error.error_code(),
+            MartosError::Timer(error) => error.error_code(),
+            MartosError::Inbox(error) => error.error_code(),
+            MartosError::Task(error) => error.error_code(),
+        }
+    }
+}
+
+impl From<SystemError> for MartosError {
+    fn from(error: SystemError) -> Self {
+        MartosError::System(error)
+    }
+}
+
+impl From<TimerError> for MartosError {
+    fn from(error: TimerError) -> Self {
+        MartosError::Timer(error)
+    }
+}
+
+impl From<InboxError> for MartosError {
+    fn from(error: InboxError) -> Self {
+        MartosError::Inbox(error)
+    }
+}
+
+impl From<TaskError> for MartosError {
+    fn from(error: TaskError) -> Self {
+        MartosError::Task(error)
+    }
+}
+
+impl fmt::Display for MartosError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MartosError::System(error) => write!(f, "system error: {}", error),
+            MartosError::Timer(error) => write!(f, "timer error: {}", error),
+            MartosError::Inbox(error) => write!(f, "inbox error: {}", error),
+            MartosError::Task(error) => write!(f, "task error: {}", error),
+        }
+    }
+}
+
+impl core::error::Error for MartosError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            MartosError::System(error) => Some(error),
+            MartosError::Timer(error) => Some(error),
+            MartosError::Inbox(error) => Some(error),
+            MartosError::Task(error) => Some(error),
+        }
+    }
+}
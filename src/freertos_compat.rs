@@ -0,0 +1,528 @@
+//! Compatibility layer, that exposes a subset of FreeRTOS C API on top of Martos.
+//! Declarations are in `c-library/martos_freertos.h`, so code written against FreeRTOS
+//! can be built with Martos by replacing FreeRTOS includes with this header.
+//!
+//! Tasks are executed by the selected task manager. Preemptive task manager executes
+//! task functions on their own stacks, so they may loop forever like in FreeRTOS.
+//! Cooperative task manager calls task function as loop function of the task, so it should
+//! do one iteration and return; [vTaskDelay] puts the task to sleep after the function returns.
+//! Functions, that have no Martos equivalent, return [ERR_UNSUPPORTED] and are reported once
+//! to the hook set by [martos_freertos_set_unsupported_hook].
+#![allow(non_snake_case)]
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::ffi::{c_char, c_void, CStr};
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
+
+use crate::ports::{Port, TimerPort};
+use crate::task_manager::{TaskIdType, TaskManager, TaskManagerTrait};
+
+/// Signed type of FreeRTOS results.
+pub type BaseType = i32;
+/// Unsigned type of FreeRTOS counts and priorities.
+pub type UBaseType = u32;
+/// Number of FreeRTOS ticks.
+pub type FreeRtosTickType = u32;
+/// Handle of a task. It is identifier of the task converted to pointer.
+pub type TaskHandle = *mut c_void;
+/// Function of FreeRTOS task, that gets parameters passed to [xTaskCreate].
+pub type TaskFunctionType = extern "C" fn(*mut c_void);
+/// Type of hook, that gets name of unsupported function as NUL-terminated string.
+pub type UnsupportedHookType = extern "C" fn(*const c_char);
+
+/// Successful result.
+pub const PD_PASS: BaseType = 1;
+/// Failed result.
+pub const PD_FAIL: BaseType = 0;
+/// True result.
+pub const PD_TRUE: BaseType = 1;
+/// False result.
+pub const PD_FALSE: BaseType = 0;
+/// Result of sending to full queue.
+pub const ERR_QUEUE_FULL: BaseType = 0;
+/// Result of receiving from empty queue.
+pub const ERR_QUEUE_EMPTY: BaseType = 0;
+/// Result of creating task, whose stack can not be allocated.
+pub const ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY: BaseType = -1;
+/// Result of functions, that have no Martos equivalent.
+pub const ERR_UNSUPPORTED: BaseType = -32;
+/// Number of ticks, that means waiting without timeout.
+pub const PORT_MAX_DELAY: FreeRtosTickType = FreeRtosTickType::MAX;
+/// Frequency of FreeRTOS ticks. A tick is one millisecond of the system clock.
+pub const TICK_RATE_HZ: u32 = 1000;
+
+/// Returns duration of the specified number of ticks.
+pub fn ticks_to_duration(ticks: FreeRtosTickType) -> Duration {
+    Duration::from_micros(ticks as u64 * 1_000_000 / TICK_RATE_HZ as u64)
+}
+
+/// Returns number of whole ticks in the duration. The result wraps like FreeRTOS tick counter.
+pub fn duration_to_ticks(duration: Duration) -> FreeRtosTickType {
+    (duration.as_micros() * TICK_RATE_HZ as u128 / 1_000_000) as FreeRtosTickType
+}
+
+/// FreeRTOS task functions with their parameters by identifiers of tasks.
+static TASK_FUNCTIONS: critical_section::Mutex<
+    RefCell<Vec<(TaskIdType, TaskFunctionType, usize)>>,
+> = critical_section::Mutex::new(RefCell::new(Vec::new()));
+
+/// Hook, that is called once per unsupported function.
+static UNSUPPORTED_HOOK: critical_section::Mutex<Cell<Option<UnsupportedHookType>>> =
+    critical_section::Mutex::new(Cell::new(None));
+
+/// Bits of unsupported functions, that are already reported to the hook.
+static UNSUPPORTED_REPORTED: AtomicU32 = AtomicU32::new(0);
+
+/// Setup function of FreeRTOS tasks.
+extern "C" fn task_setup_fn() {}
+
+/// Loop function of FreeRTOS tasks. Calls task function of the current task with its parameters.
+extern "C" fn task_loop_fn() {
+    let Some(id) = TaskManager::current_task_id() else {
+        return;
+    };
+    let entry = critical_section::with(|cs| {
+        TASK_FUNCTIONS
+            .borrow_ref(cs)
+            .iter()
+            .find(|(task_id, _, _)| *task_id == id)
+            .map(|&(_, task_function, parameters)| (task_function, parameters))
+    });
+    if let Some((task_function, parameters)) = entry {
+        task_function(parameters as *mut c_void);
+    }
+}
+
+/// Stop condition function of FreeRTOS tasks. They are stopped only by [vTaskDelete].
+extern "C" fn task_stop_condition_fn() -> bool {
+    false
+}
+
+/// Creates task, that calls the task function with the parameters. Martos is initialized,
+/// if it is not yet. On preemptive task manager stack of the task has `stack_depth` pointer-sized
+/// words and priority is truncated to maximum priority of threads. Cooperative tasks share one stack
+/// and have no priorities, so stack depth and priority are ignored there, and the name is given to the task.
+/// Returns [PD_PASS] or [ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY] for zero stack depth.
+/// # Safety
+/// `name` should point to a NUL-terminated string or be null. `created_task` should point
+/// to writable handle or be null.
+#[no_mangle]
+pub unsafe extern "C" fn xTaskCreate(
+    task_function: TaskFunctionType,
+    name: *const c_char,
+    stack_depth: u16,
+    parameters: *mut c_void,
+    priority: UBaseType,
+    created_task: *mut TaskHandle,
+) -> BaseType {
+    if stack_depth == 0 {
+        return ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY;
+    }
+    crate::init_system();
+    // The entry is added together with the task, so preempted task never misses its function.
+    let id = critical_section::with(|cs| {
+        let id = add_task(name, stack_depth, priority);
+        let mut task_functions = TASK_FUNCTIONS.borrow_ref_mut(cs);
+        // Identifiers start again after task manager is reset, so stale entries are replaced.
+        task_functions.retain(|(task_id, _, _)| *task_id != id);
+        task_functions.push((id, task_function, parameters as usize));
+        id
+    });
+    if !created_task.is_null() {
+        *created_task = id as TaskHandle;
+    }
+    PD_PASS
+}
+
+/// Adds task with FreeRTOS task functions to cooperative task manager.
+#[cfg(not(feature = "preemptive"))]
+unsafe fn add_task(name: *const c_char, _stack_depth: u16, _priority: UBaseType) -> TaskIdType {
+    let name = if name.is_null() {
+        &[]
+    } else {
+        CStr::from_ptr(name).to_bytes()
+    };
+    TaskManager::add_task_with_name(
+        crate::task_manager::cooperative::TaskName::from_bytes(name),
+        task_setup_fn,
+        task_loop_fn,
+        task_stop_condition_fn,
+    )
+}
+
+/// Adds thread with FreeRTOS task functions to preemptive task manager.
+#[cfg(feature = "preemptive")]
+unsafe fn add_task(_name: *const c_char, stack_depth: u16, priority: UBaseType) -> TaskIdType {
+    TaskManager::add_thread(
+        task_setup_fn,
+        task_loop_fn,
+        task_stop_condition_fn,
+        priority.min(crate::task_manager::MAX_TASK_PRIORITY as UBaseType) as u8,
+        stack_depth as usize * core::mem::size_of::<usize>(),
+    )
+}
+
+/// Terminates the task, or the current task if the handle is null.
+/// On preemptive task manager the current task does not return from this function.
+/// On cooperative task manager the current task is removed after its task function returns.
+#[no_mangle]
+pub extern "C" fn vTaskDelete(task: TaskHandle) {
+    let id = if task.is_null() {
+        TaskManager::current_task_id()
+    } else {
+        Some(task as TaskIdType)
+    };
+    let Some(id) = id else {
+        return;
+    };
+    let _ = TaskManager::terminate_task(id);
+    critical_section::with(|cs| {
+        TASK_FUNCTIONS
+            .borrow_ref_mut(cs)
+            .retain(|(task_id, _, _)| *task_id != id)
+    });
+    #[cfg(feature = "preemptive")]
+    if task.is_null() {
+        // Terminated thread is removed at the next context switch.
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Delays the current task for the specified number of ticks. Other tasks are executed meanwhile.
+/// On cooperative task manager the task is put to sleep and its task function should return.
+/// Outside of tasks or without the system clock it busy-waits.
+#[no_mangle]
+pub extern "C" fn vTaskDelay(ticks: FreeRtosTickType) {
+    let duration = ticks_to_duration(ticks);
+    #[cfg(not(feature = "preemptive"))]
+    match TaskManager::current_task_id() {
+        Some(id) if TaskManager::try_sleep_for(id, duration).is_ok() => {}
+        _ => crate::timer::delay_us(duration.as_micros() as u64),
+    }
+    #[cfg(feature = "preemptive")]
+    TaskManager::sleep_current_for(duration);
+}
+
+/// Returns number of ticks since Martos initialization or 0 if the port has no system clock.
+#[no_mangle]
+pub extern "C" fn xTaskGetTickCount() -> FreeRtosTickType {
+    crate::ports::clock_timer().map_or(0, |timer_index| {
+        duration_to_ticks(Port::get_time(timer_index))
+    })
+}
+
+/// Initializes Martos, if it is not yet, and starts task manager.
+#[no_mangle]
+pub extern "C" fn vTaskStartScheduler() {
+    crate::init_system();
+    TaskManager::start_task_manager()
+}
+
+/// Queue of fixed-size items. Binary semaphore is a queue of one item of zero size.
+pub struct Queue {
+    /// Maximum number of items.
+    length: usize,
+    /// Size of every item in bytes.
+    item_size: usize,
+    /// State of the queue, that is shared with interrupt handlers.
+    state: critical_section::Mutex<RefCell<QueueState>>,
+}
+
+/// Items of the queue.
+struct QueueState {
+    /// Bytes of the items in order of receiving.
+    bytes: VecDeque<u8>,
+    /// Number of the items.
+    count: usize,
+}
+
+impl Queue {
+    /// Copies the item to the back or to the front of the queue. Returns false if the queue is full.
+    unsafe fn send(&self, item: *const c_void, to_front: bool) -> bool {
+        critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            if state.count == self.length {
+                return false;
+            }
+            let byte = |index| {
+                if item.is_null() {
+                    0
+                } else {
+                    *(item as *const u8).add(index)
+                }
+            };
+            if to_front {
+                for index in (0..self.item_size).rev() {
+                    state.bytes.push_front(byte(index));
+                }
+            } else {
+                for index in 0..self.item_size {
+                    state.bytes.push_back(byte(index));
+                }
+            }
+            state.count += 1;
+            true
+        })
+    }
+
+    /// Moves the first item of the queue to the buffer. Returns false if the queue is empty.
+    unsafe fn receive(&self, buffer: *mut c_void) -> bool {
+        critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            if state.count == 0 {
+                return false;
+            }
+            for index in 0..self.item_size {
+                let byte = state.bytes.pop_front().unwrap();
+                if !buffer.is_null() {
+                    *(buffer as *mut u8).add(index) = byte;
+                }
+            }
+            state.count -= 1;
+            true
+        })
+    }
+
+    /// Returns number of items in the queue.
+    fn count(&self) -> usize {
+        critical_section::with(|cs| self.state.borrow_ref(cs).count)
+    }
+}
+
+/// Tries the operation and repeats it once per tick until it succeeds or the ticks pass.
+/// Cooperative tasks can not block inside loop function, so the operation is tried once there.
+fn retry_for_ticks(ticks: FreeRtosTickType, mut operation: impl FnMut() -> bool) -> bool {
+    if operation() {
+        return true;
+    }
+    #[cfg(feature = "preemptive")]
+    {
+        let mut waited = 0;
+        while ticks == PORT_MAX_DELAY || waited < ticks {
+            TaskManager::sleep_current_for(ticks_to_duration(1));
+            waited += 1;
+            if operation() {
+                return true;
+            }
+        }
+    }
+    #[cfg(not(feature = "preemptive"))]
+    let _ = ticks;
+    false
+}
+
+/// Creates queue for `length` items of `item_size` bytes. Martos is initialized, if it is not yet.
+/// Returns null for zero length.
+#[no_mangle]
+pub extern "C" fn xQueueCreate(length: UBaseType, item_size: UBaseType) -> *mut Queue {
+    if length == 0 {
+        return core::ptr::null_mut();
+    }
+    crate::init_system();
+    Box::into_raw(Box::new(Queue {
+        length: length as usize,
+        item_size: item_size as usize,
+        state: critical_section::Mutex::new(RefCell::new(QueueState {
+            bytes: VecDeque::new(),
+            count: 0,
+        })),
+    }))
+}
+
+/// Deletes the queue.
+/// # Safety
+/// `queue` should be created by [xQueueCreate] and not be used after deleting.
+#[no_mangle]
+pub unsafe extern "C" fn vQueueDelete(queue: *mut Queue) {
+    if !queue.is_null() {
+        drop(Box::from_raw(queue));
+    }
+}
+
+/// Copies the item to the back of the queue, waiting for free space up to the specified number of ticks
+/// on preemptive task manager. Returns [PD_PASS] or [ERR_QUEUE_FULL].
+/// # Safety
+/// `queue` should be created by [xQueueCreate], `item` should point to item of the queue size.
+#[no_mangle]
+pub unsafe extern "C" fn xQueueSend(
+    queue: *mut Queue,
+    item: *const c_void,
+    ticks_to_wait: FreeRtosTickType,
+) -> BaseType {
+    let queue = &*queue;
+    if retry_for_ticks(ticks_to_wait, || queue.send(item, false)) {
+        PD_PASS
+    } else {
+        ERR_QUEUE_FULL
+    }
+}
+
+/// Copies the item to the front of the queue like [xQueueSend].
+/// # Safety
+/// `queue` should be created by [xQueueCreate], `item` should point to item of the queue size.
+#[no_mangle]
+pub unsafe extern "C" fn xQueueSendToFront(
+    queue: *mut Queue,
+    item: *const c_void,
+    ticks_to_wait: FreeRtosTickType,
+) -> BaseType {
+    let queue = &*queue;
+    if retry_for_ticks(ticks_to_wait, || queue.send(item, true)) {
+        PD_PASS
+    } else {
+        ERR_QUEUE_FULL
+    }
+}
+
+/// Copies the item to the back of the queue from interrupt handler without waiting.
+/// Sets `higher_priority_task_woken` to [PD_FALSE], because tasks are switched only by task manager.
+/// # Safety
+/// `queue` should be created by [xQueueCreate], `item` should point to item of the queue size,
+/// `higher_priority_task_woken` should point to writable value or be null.
+#[no_mangle]
+pub unsafe extern "C" fn xQueueSendFromISR(
+    queue: *mut Queue,
+    item: *const c_void,
+    higher_priority_task_woken: *mut BaseType,
+) -> BaseType {
+    if !higher_priority_task_woken.is_null() {
+        *higher_priority_task_woken = PD_FALSE;
+    }
+    if (*queue).send(item, false) {
+        PD_PASS
+    } else {
+        ERR_QUEUE_FULL
+    }
+}
+
+/// Moves the first item of the queue to the buffer, waiting for it up to the specified number of ticks
+/// on preemptive task manager. Returns [PD_PASS] or [ERR_QUEUE_EMPTY].
+/// # Safety
+/// `queue` should be created by [xQueueCreate], `buffer` should point to writable item of the queue size.
+#[no_mangle]
+pub unsafe extern "C" fn xQueueReceive(
+    queue: *mut Queue,
+    buffer: *mut c_void,
+    ticks_to_wait: FreeRtosTickType,
+) -> BaseType {
+    let queue = &*queue;
+    if retry_for_ticks(ticks_to_wait, || queue.receive(buffer)) {
+        PD_PASS
+    } else {
+        ERR_QUEUE_EMPTY
+    }
+}
+
+/// Returns number of items in the queue.
+/// # Safety
+/// `queue` should be created by [xQueueCreate].
+#[no_mangle]
+pub unsafe extern "C" fn uxQueueMessagesWaiting(queue: *const Queue) -> UBaseType {
+    (*queue).count() as UBaseType
+}
+
+/// Creates binary semaphore, that is initially taken. Martos is initialized, if it is not yet.
+#[no_mangle]
+pub extern "C" fn xSemaphoreCreateBinary() -> *mut Queue {
+    xQueueCreate(1, 0)
+}
+
+/// Deletes the semaphore.
+/// # Safety
+/// `semaphore` should be created by [xSemaphoreCreateBinary] and not be used after deleting.
+#[no_mangle]
+pub unsafe extern "C" fn vSemaphoreDelete(semaphore: *mut Queue) {
+    vQueueDelete(semaphore)
+}
+
+/// Takes the semaphore, waiting for it up to the specified number of ticks on preemptive
+/// task manager. Returns [PD_TRUE] or [PD_FALSE].
+/// # Safety
+/// `semaphore` should be created by [xSemaphoreCreateBinary].
+#[no_mangle]
+pub unsafe extern "C" fn xSemaphoreTake(
+    semaphore: *mut Queue,
+    ticks_to_wait: FreeRtosTickType,
+) -> BaseType {
+    let semaphore = &*semaphore;
+    if retry_for_ticks(ticks_to_wait, || semaphore.receive(core::ptr::null_mut())) {
+        PD_TRUE
+    } else {
+        PD_FALSE
+    }
+}
+
+/// Gives the semaphore. Returns [PD_FALSE] if it is already given.
+/// # Safety
+/// `semaphore` should be created by [xSemaphoreCreateBinary].
+#[no_mangle]
+pub unsafe extern "C" fn xSemaphoreGive(semaphore: *mut Queue) -> BaseType {
+    if (*semaphore).send(core::ptr::null(), false) {
+        PD_TRUE
+    } else {
+        PD_FALSE
+    }
+}
+
+/// Gives the semaphore from interrupt handler like [xSemaphoreGive].
+/// Sets `higher_priority_task_woken` to [PD_FALSE], because tasks are switched only by task manager.
+/// # Safety
+/// `semaphore` should be created by [xSemaphoreCreateBinary],
+/// `higher_priority_task_woken` should point to writable value or be null.
+#[no_mangle]
+pub unsafe extern "C" fn xSemaphoreGiveFromISR(
+    semaphore: *mut Queue,
+    higher_priority_task_woken: *mut BaseType,
+) -> BaseType {
+    if !higher_priority_task_woken.is_null() {
+        *higher_priority_task_woken = PD_FALSE;
+    }
+    xSemaphoreGive(semaphore)
+}
+
+/// Sets hook, that is called once per unsupported function, when it is called for the first time
+/// after the hook is set. Hook may be null.
+#[no_mangle]
+pub extern "C" fn martos_freertos_set_unsupported_hook(hook: Option<UnsupportedHookType>) {
+    critical_section::with(|cs| UNSUPPORTED_HOOK.borrow(cs).set(hook));
+    UNSUPPORTED_REPORTED.store(0, Ordering::Relaxed);
+}
+
+/// Reports the unsupported function with the specified bit to the hook, if it is not reported yet.
+fn unsupported(name: &'static CStr, bit: u32) -> BaseType {
+    if UNSUPPORTED_REPORTED.fetch_or(1 << bit, Ordering::Relaxed) & (1 << bit) == 0 {
+        if let Some(hook) = critical_section::with(|cs| UNSUPPORTED_HOOK.borrow(cs).get()) {
+            hook(name.as_ptr());
+        }
+    }
+    ERR_UNSUPPORTED
+}
+
+/// Task notifications are not supported. Always returns [ERR_UNSUPPORTED].
+#[no_mangle]
+pub extern "C" fn xTaskNotifyGive(_task: TaskHandle) -> BaseType {
+    unsupported(c"xTaskNotifyGive", 0)
+}
+
+/// Task notifications are not supported. Always returns [ERR_UNSUPPORTED].
+#[no_mangle]
+pub extern "C" fn xTaskNotify(_task: TaskHandle, _value: u32, _action: i32) -> BaseType {
+    unsupported(c"xTaskNotify", 1)
+}
+
+/// Task notifications are not supported. Always returns [ERR_UNSUPPORTED].
+#[no_mangle]
+pub extern "C" fn xTaskNotifyWait(
+    _bits_to_clear_on_entry: u32,
+    _bits_to_clear_on_exit: u32,
+    _notification_value: *mut u32,
+    _ticks_to_wait: FreeRtosTickType,
+) -> BaseType {
+    unsupported(c"xTaskNotifyWait", 2)
+}
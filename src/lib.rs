@@ -1,25 +1,69 @@
 #![no_std]
 extern crate alloc;
 
+use core::sync::atomic::{AtomicBool, Ordering};
+
+mod config_check;
 mod ports;
-use ports::PortTrait;
+#[cfg(feature = "network")]
+use ports::NetworkPort;
+use ports::{HeapPort, TimerPort};
 #[cfg(feature = "c-library")]
 pub mod c_api;
+pub mod diagnostics;
+pub mod error;
+#[cfg(feature = "freertos-compat")]
+pub mod freertos_compat;
 pub mod task_manager;
 pub mod timer;
+mod version;
 #[cfg(any(target_arch = "riscv32", target_arch = "xtensa"))]
 #[cfg(feature = "network")]
 use esp_wifi::esp_now::EspNow;
+pub use version::{
+    set_version_logger, version, VersionInfo, VersionLoggerType, MARTOS_ABI_VERSION,
+};
+
+/// Indicator whether Martos is initialized.
+static SYSTEM_INITIALIZED: AtomicBool = AtomicBool::new(false);
 
 /// Martos initialization. Should be called before using Martos functions.
-pub fn init_system() {
+/// Repeated calls do nothing until [shutdown_system] is called.
+/// Information about Martos build is passed to the logger set by [set_version_logger].
+/// Returns true if this call initialized Martos and false if it was already initialized.
+pub fn init_system() -> bool {
+    if SYSTEM_INITIALIZED.swap(true, Ordering::AcqRel) {
+        return false;
+    }
     // Memory initialization.
     ports::Port::init_heap();
     // Hardware timer setup.
     ports::Port::setup_hardware_timer();
+    // Reservation of hardware timers used by Martos itself.
+    for &(timer_index, owner) in ports::SYSTEM_TIMERS {
+        ports::Port::reserve_timer(timer_index, owner);
+    }
+    // System clock counts from initialization.
+    if let Some(timer_index) = ports::clock_timer() {
+        ports::Port::start_hardware_timer(timer_index);
+    }
     #[cfg(feature = "network")]
     // Network setup.
     ports::Port::init_network();
+    version::log_version();
+    true
+}
+
+/// Martos shutdown. Releases all timers and removes all tasks, so the next [init_system] call
+/// starts from a clean state. Should not be called while task manager is running.
+/// Returns an error if the platform doesn't support re-initialization. In this case nothing is changed.
+pub fn shutdown_system() -> Result<(), error::SystemError> {
+    if !ports::Port::reset_hardware_timers() {
+        return Err(error::SystemError::ReinitUnsupported);
+    }
+    task_manager::TaskManager::reset();
+    SYSTEM_INITIALIZED.store(false, Ordering::Release);
+    Ok(())
 }
 
 #[cfg(any(target_arch = "riscv32", target_arch = "xtensa"))]
@@ -0,0 +1,47 @@
+//! Minimal port. It is a starting point for a new platform: one software timer without
+//! hardware behind it, no heap setup, default implementations of optional timer functions
+//! and optional capabilities implemented by the `unsupported_*` helper macros.
+//! A real port replaces the bodies with hardware access.
+
+use crate::ports::{HeapPort, PortTrait, TimerPort};
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+// Declare minimal_port_tests file as child file to test private functions.
+#[cfg(test)]
+#[path = "../../../tests/minimal/minimal_port_tests.rs"]
+mod minimal_port_tests;
+
+/// Indicator showing whether the timer is in use.
+static TIMER_IN_USE: AtomicBool = AtomicBool::new(false);
+
+/// PortTrait implementation for minimal platform.
+pub struct Minimal;
+impl PortTrait for Minimal {}
+
+impl HeapPort for Minimal {
+    fn init_heap() {}
+}
+
+impl TimerPort for Minimal {
+    fn setup_hardware_timer() {}
+
+    fn try_acquire_timer(_timer_index: u8) -> bool {
+        TIMER_IN_USE
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn get_time(_timer_index: u8) -> Duration {
+        Duration::ZERO
+    }
+
+    fn release_hardware_timer(_timer_index: u8) {
+        TIMER_IN_USE.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(feature = "network")]
+unsupported_network_port!(Minimal);
+#[cfg(feature = "preemptive")]
+unsupported_preempt_port!(Minimal);
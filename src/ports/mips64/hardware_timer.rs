@@ -1,4 +1,4 @@
-use crate::timer::TickType;
+use crate::timer::{TickType, TimerError, TimerSlotState};
 use core::sync::atomic::{AtomicBool, Ordering};
 use core::time::Duration;
 
@@ -10,6 +10,9 @@ mod mips64_timer_tests;
 /// Static variable for storing an instance of the timer block.
 static mut TIMER_BLOCK: Option<TimerBlock<MemoryAccess>> = None;
 
+/// Number of timers in the timer block.
+pub const TIMER_COUNT: u8 = 5;
+
 /// Base address of timer 0.
 const TIMER_0: u64 = 0x01B400080;
 /// Base address of timer 1.
@@ -27,6 +30,13 @@ const CONFIGURATION_REGISTERS: u64 = 0x01B4000D0;
 const STATUS_AND_CONTROL_REGISTER_OFFSET: u64 = 0x08;
 /// Standard frequency of timer operation - 4 MHz.
 const TIMER_FREQUENCY: u64 = 4;
+/// Frequency of the clock, that drives the timers.
+pub const SOURCE_CLOCK_HZ: u32 = TIMER_FREQUENCY as u32 * 1_000_000;
+/// Effective divider of the timer clock. Counter value is converted to whole microseconds, so it is fixed.
+pub const TIMER_DIVIDER: u32 = TIMER_FREQUENCY as u32;
+/// Nominal CPU frequency.
+// TODO: Should be taken from board configuration.
+pub const CPU_FREQUENCY_HZ: u32 = 1_000_000_000;
 
 /// Structure representing a block of timers.
 struct TimerBlock<M: ByteAccess> {
@@ -75,6 +85,9 @@ struct Timer<M: ByteAccess> {
     /// An indicator showing whether the timer is in use.
     in_use: AtomicBool,
 
+    /// Name of Martos subsystem owning the timer, if the timer is reserved.
+    owner: Option<&'static str>,
+
     /// An indicator showing whether the timer is in auto reload mode or in one shot mode.
     reload_mode: bool,
 
@@ -96,6 +109,7 @@ impl<M: ByteAccess> Timer<M> {
             duration: 0,
             resolution_mask: enable_mask,
             in_use: AtomicBool::new(false),
+            owner: None,
             reload_mode: false,
             lock_for_load: AtomicBool::new(false),
             lock_for_now: AtomicBool::new(false),
@@ -103,6 +117,29 @@ impl<M: ByteAccess> Timer<M> {
         }
     }
 
+    /// Reserves the timer for Martos subsystem.
+    fn reserve(&mut self, owner: &'static str) -> bool {
+        if self
+            .in_use
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.owner = Some(owner);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Gets the state of the timer.
+    fn slot_state(&self) -> TimerSlotState {
+        match self.owner {
+            Some(owner) => TimerSlotState::Reserved(owner),
+            None if self.in_use.load(Ordering::Relaxed) => TimerSlotState::UserAcquired,
+            None => TimerSlotState::Free,
+        }
+    }
+
     /// Enables the timer counting.
     fn start(&mut self) {
         if self.duration == 0 {
@@ -258,7 +295,7 @@ pub fn setup_hardware_timer() {
 
 /// Mips64 attempt to acquire timer.
 pub fn try_acquire_timer(timer_index: u8) -> bool {
-    if timer_index <= 4 as u8 {
+    if timer_index < TIMER_COUNT {
         unsafe {
             let timer_block = TIMER_BLOCK.take().expect("Timer block error");
 
@@ -288,6 +325,42 @@ pub fn try_acquire_timer(timer_index: u8) -> bool {
     }
 }
 
+/// Mips64 reserve timer for Martos subsystem.
+pub fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+    unsafe {
+        let mut timer_block = TIMER_BLOCK.take().expect("Timer block error");
+        let return_value = match timer_index {
+            0 => timer_block.timer0.reserve(owner),
+            1 => timer_block.timer1.reserve(owner),
+            2 => timer_block.timer2.reserve(owner),
+            3 => timer_block.timer3.reserve(owner),
+            4 => timer_block.timer4.reserve(owner),
+            _ => false,
+        };
+        TIMER_BLOCK = Some(timer_block);
+
+        return_value
+    }
+}
+
+/// Mips64 getting state of timer.
+pub fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+    unsafe {
+        let timer_block = TIMER_BLOCK.take().expect("Timer block error");
+        let state = match timer_index {
+            0 => timer_block.timer0.slot_state(),
+            1 => timer_block.timer1.slot_state(),
+            2 => timer_block.timer2.slot_state(),
+            3 => timer_block.timer3.slot_state(),
+            4 => timer_block.timer4.slot_state(),
+            _ => TimerSlotState::Free,
+        };
+        TIMER_BLOCK = Some(timer_block);
+
+        state
+    }
+}
+
 /// Mips64 start harware timer.
 pub fn start_hardware_timer(timer_index: u8) {
     unsafe {
@@ -337,6 +410,15 @@ pub fn change_period_timer(timer_index: u8, period: Duration) {
     }
 }
 
+/// Mips64 change divider of the timer clock. Only the fixed divider is supported.
+pub fn set_timer_divider(divider: u32) -> Result<(), TimerError> {
+    if divider == TIMER_DIVIDER {
+        Ok(())
+    } else {
+        Err(TimerError::UnsupportedResolution)
+    }
+}
+
 /// Mips64 getting counter value of hardware timer.
 pub fn get_time(timer_index: u8) -> Duration {
     unsafe {
@@ -388,3 +470,12 @@ pub fn release_hardware_timer(timer_index: u8) {
         TIMER_BLOCK = Some(timer_block);
     }
 }
+
+/// Mips64 busy-wait delay.
+/// Spins assuming one loop iteration per CPU cycle, so the delay is not shorter than requested.
+pub fn delay_us(us: u64) {
+    let cycles = us.saturating_mul((CPU_FREQUENCY_HZ / 1_000_000) as u64);
+    for _ in 0..cycles {
+        core::hint::spin_loop();
+    }
+}
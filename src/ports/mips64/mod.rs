@@ -1,24 +1,31 @@
 pub mod hardware_timer;
 #[cfg(not(feature = "mips64_timer_tests"))]
 pub mod memory_manager;
-#[cfg(feature = "network")]
-pub mod network;
-use crate::ports::PortTrait;
+use crate::ports::{HeapPort, PortTrait, TimerPort};
+use crate::timer::{TimerError, TimerSlotState};
 
 /// PortTrait implementation for Mips64 platform
 pub struct Mips64;
-impl PortTrait for Mips64 {
+impl PortTrait for Mips64 {}
+
+impl HeapPort for Mips64 {
     fn init_heap() {
         #[cfg(not(feature = "mips64_timer_tests"))]
         memory_manager::init_heap();
     }
+}
 
+impl TimerPort for Mips64 {
     fn setup_hardware_timer() {
         hardware_timer::setup_hardware_timer();
     }
 
+    fn timer_count() -> u8 {
+        hardware_timer::TIMER_COUNT
+    }
+
     fn valid_timer_index(timer_index: u8) -> bool {
-        if timer_index <= 4 {
+        if timer_index < hardware_timer::TIMER_COUNT {
             true
         } else {
             false
@@ -29,6 +36,14 @@ impl PortTrait for Mips64 {
         hardware_timer::try_acquire_timer(timer_index)
     }
 
+    fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+        hardware_timer::reserve_timer(timer_index, owner)
+    }
+
+    fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+        hardware_timer::timer_slot_state(timer_index)
+    }
+
     fn start_hardware_timer(timer_index: u8) {
         hardware_timer::start_hardware_timer(timer_index);
     }
@@ -45,6 +60,18 @@ impl PortTrait for Mips64 {
         hardware_timer::get_time(timer_index)
     }
 
+    fn timer_source_clock_hz() -> u32 {
+        hardware_timer::SOURCE_CLOCK_HZ
+    }
+
+    fn timer_divider(_timer_index: u8) -> u32 {
+        hardware_timer::TIMER_DIVIDER
+    }
+
+    fn set_timer_divider(_timer_index: u8, divider: u32) -> Result<(), TimerError> {
+        hardware_timer::set_timer_divider(divider)
+    }
+
     fn stop_hardware_timer(timer_index: u8) -> bool {
         hardware_timer::stop_hardware_timer(timer_index)
     }
@@ -53,8 +80,32 @@ impl PortTrait for Mips64 {
         hardware_timer::release_hardware_timer(timer_index)
     }
 
-    #[cfg(feature = "network")]
-    fn init_network() {
-        network::init_network();
+    fn reset_hardware_timers() -> bool {
+        false
+    }
+
+    fn cpu_freq_hz() -> u32 {
+        hardware_timer::CPU_FREQUENCY_HZ
+    }
+
+    fn delay_us(us: u64) {
+        hardware_timer::delay_us(us);
     }
 }
+
+#[cfg(feature = "network")]
+unsupported_network_port!(Mips64);
+
+/// Critical section implementation for Mips64 platform. Martos runs on a single core there
+/// and does not enable interrupts, so nothing can preempt the code inside of critical section.
+#[cfg(target_arch = "mips64")]
+struct SingleCoreCriticalSection;
+#[cfg(target_arch = "mips64")]
+critical_section::set_impl!(SingleCoreCriticalSection);
+
+#[cfg(target_arch = "mips64")]
+unsafe impl critical_section::Impl for SingleCoreCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {}
+
+    unsafe fn release(_restore_state: critical_section::RawRestoreState) {}
+}
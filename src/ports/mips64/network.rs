@@ -1,2 +0,0 @@
-/// Network initialization.
-pub fn init_network() {}
@@ -1,50 +1,232 @@
+use crate::timer::{TimerError, TimerSlotState};
 use core::time::Duration;
 #[cfg(any(target_arch = "riscv32", target_arch = "xtensa"))]
 #[cfg(feature = "network")]
 use esp_wifi::esp_now::EspNow;
 
-/// PortTrait contains all the platform specific functions.
-pub trait PortTrait {
+/// PortTrait is implemented by every platform. It combines capabilities, that are required by Martos
+/// regardless of the enabled features. Optional capabilities are separate traits, that are checked
+/// only when the corresponding feature is enabled (see [assert_port_capabilities]).
+pub trait PortTrait: TimerPort + HeapPort {}
+
+/// TimerPort contains platform specific functions for hardware timers.
+/// Only setup, acquisition, release and time reading are required. Other functions have defaults
+/// for platforms without the corresponding timer features.
+pub trait TimerPort {
     /// Function is called when timer is created. Can be used to set configuration.
     fn setup_hardware_timer();
+    /// Function is used to get the number of hardware timers. By default the platform has one timer.
+    fn timer_count() -> u8 {
+        1
+    }
     /// Function is used to check the correctness of index.
-    fn valid_timer_index(timer_index: u8) -> bool;
+    fn valid_timer_index(timer_index: u8) -> bool {
+        timer_index < Self::timer_count()
+    }
     /// Function is called to attempt to acquire the timer.
     fn try_acquire_timer(timer_index: u8) -> bool;
-    /// Function is called to start the timer.
-    fn start_hardware_timer(timer_index: u8);
-    /// Function is called to change the timer operating mode.
-    fn set_reload_mode(timer_index: u8, auto_reload: bool);
-    /// Function is called to change the period of the timer.
-    fn change_period_timer(timer_index: u8, period: Duration);
+    /// Function is called to reserve the timer for internal usage by Martos subsystem.
+    /// By default reservation is not supported.
+    fn reserve_timer(_timer_index: u8, _owner: &'static str) -> bool {
+        false
+    }
+    /// Function is used to get the state of the timer.
+    /// By default states are not tracked and every timer is reported as free.
+    fn timer_slot_state(_timer_index: u8) -> TimerSlotState {
+        TimerSlotState::Free
+    }
+    /// Function is called to start the timer. By default does nothing.
+    fn start_hardware_timer(_timer_index: u8) {}
+    /// Function is called to change the timer operating mode. By default does nothing.
+    fn set_reload_mode(_timer_index: u8, _auto_reload: bool) {}
+    /// Function is called to change the period of the timer. By default does nothing.
+    fn change_period_timer(_timer_index: u8, _period: Duration) {}
     /// Function is called to get amount of time from the start of the timer.
     fn get_time(timer_index: u8) -> Duration;
-    /// Function is called to stop the timer.
-    fn stop_hardware_timer(timer_index: u8) -> bool;
+    /// Function is used to get the frequency of the clock, that drives the timers, in hertz.
+    /// By default timers count microseconds.
+    fn timer_source_clock_hz() -> u32 {
+        1_000_000
+    }
+    /// Function is used to get the divider of the source clock for the timer. By default it is 1.
+    fn timer_divider(_timer_index: u8) -> u32 {
+        1
+    }
+    /// Function is called to change the divider of the source clock for the timer.
+    /// Should fail if the timer is started or the divider is not supported.
+    /// By default only divider 1 is supported.
+    fn set_timer_divider(_timer_index: u8, divider: u32) -> Result<(), TimerError> {
+        if divider == 1 {
+            Ok(())
+        } else {
+            Err(TimerError::UnsupportedResolution)
+        }
+    }
+    /// Function is called to stop the timer. By default stopping is not supported and false is returned.
+    fn stop_hardware_timer(_timer_index: u8) -> bool {
+        false
+    }
     /// Function is called to release the timer.
     fn release_hardware_timer(timer_index: u8);
+    /// Function is called on system shutdown to release all timers, including reserved ones,
+    /// and to reset the clock. Returns false if the platform doesn't support re-initialization,
+    /// that is the default.
+    fn reset_hardware_timers() -> bool {
+        false
+    }
+    /// Function is used to get the CPU frequency in hertz. By default it is unknown and 0 is returned.
+    fn cpu_freq_hz() -> u32 {
+        0
+    }
+    /// Function is called to busy-wait for the specified number of microseconds.
+    /// By default spins as many iterations as the CPU has cycles in the specified time.
+    /// Every iteration takes at least one cycle, so the delay is at least the specified time,
+    /// but it may be several times longer. Returns immediately, if the CPU frequency is unknown.
+    fn delay_us(us: u64) {
+        let cycles = us.saturating_mul((Self::cpu_freq_hz() / 1_000_000) as u64);
+        for _ in 0..cycles {
+            core::hint::spin_loop();
+        }
+    }
+}
 
+/// HeapPort contains platform specific functions for memory allocation.
+pub trait HeapPort {
     /// Function is called when heap is created. Can be used to set configuration.
     fn init_heap();
-    #[cfg(feature = "network")]
+}
+
+/// NetworkPort contains platform specific functions for network. Required by `network` feature.
+#[cfg(feature = "network")]
+pub trait NetworkPort {
     /// Function for initializing network settings.
     fn init_network();
     #[cfg(any(target_arch = "riscv32", target_arch = "xtensa"))]
-    #[cfg(feature = "network")]
     /// Function for getting esp-now object for network.
     fn get_esp_now() -> EspNow<'static>;
+}
 
-    // TODO: split to separate trait?
-    #[cfg(feature = "preemptive")]
+/// PreemptPort contains platform specific functions for preemptive task manager.
+/// Required by `preemptive` feature.
+#[cfg(feature = "preemptive")]
+pub trait PreemptPort {
+    /// Function is called to set up the timer interrupt, that calls scheduler.
     fn setup_interrupt();
-    #[cfg(feature = "preemptive")]
+    /// Function is called to prepare stack and initial context of the thread.
     fn setup_stack(thread: &mut crate::task_manager::preemptive::Thread);
-    #[cfg(feature = "preemptive")]
+    /// Function is called to save context of the interrupted thread.
     fn save_ctx(thread_ctx: &mut TrapFrame, isr_ctx: &TrapFrame);
-    #[cfg(feature = "preemptive")]
+    /// Function is called to load context of the next thread.
     fn load_ctx(thread_ctx: &TrapFrame, isr_ctx: &mut TrapFrame);
 }
 
+/// Implements [NetworkPort] for a platform without network. Network initialization does nothing.
+#[cfg(feature = "network")]
+#[allow(unused_macros)]
+macro_rules! unsupported_network_port {
+    ($port:ty) => {
+        impl $crate::ports::NetworkPort for $port {
+            fn init_network() {}
+        }
+    };
+}
+
+/// Implements [PreemptPort] for a platform without context switching. All functions do nothing.
+#[cfg(feature = "preemptive")]
+#[allow(unused_macros)]
+macro_rules! unsupported_preempt_port {
+    ($port:ty) => {
+        impl $crate::ports::PreemptPort for $port {
+            fn setup_interrupt() {}
+            fn setup_stack(_thread: &mut $crate::task_manager::preemptive::Thread) {}
+            fn save_ctx(
+                _thread_ctx: &mut $crate::ports::TrapFrame,
+                _isr_ctx: &$crate::ports::TrapFrame,
+            ) {
+            }
+            fn load_ctx(
+                _thread_ctx: &$crate::ports::TrapFrame,
+                _isr_ctx: &mut $crate::ports::TrapFrame,
+            ) {
+            }
+        }
+    };
+}
+
+/// Compile-time check, that the port of the current platform supports all enabled features.
+#[allow(dead_code)]
+fn assert_port_capabilities() {
+    fn is_port<P: PortTrait>() {}
+    is_port::<Port>();
+    #[cfg(feature = "network")]
+    {
+        fn is_network_port<P: NetworkPort>() {}
+        is_network_port::<Port>();
+    }
+    #[cfg(feature = "preemptive")]
+    {
+        fn is_preempt_port<P: PreemptPort>() {}
+        is_preempt_port::<Port>();
+    }
+}
+
+/// Checks that every system timer has a valid index and is reserved by only one subsystem.
+/// Every timer index of a port corresponds to a separate hardware timer unit,
+/// so a timer acquired by user never shares hardware with a reserved one.
+pub(crate) const fn valid_system_timers(system_timers: &[(u8, &str)], timer_count: u8) -> bool {
+    let mut i = 0;
+    while i < system_timers.len() {
+        if system_timers[i].0 >= timer_count {
+            return false;
+        }
+        let mut j = i + 1;
+        while j < system_timers.len() {
+            if system_timers[i].0 == system_timers[j].0 {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Owner name of the hardware timer, that is reserved as system clock.
+/// The clock measures uptime for timed sleeps of tasks and for scheduler instrumentation.
+pub(crate) const CLOCK_TIMER_OWNER: &str = "clock";
+
+/// Returns index of the system timer with the specified owner or None if there is no such timer.
+pub(crate) const fn find_system_timer(system_timers: &[(u8, &str)], owner: &str) -> Option<u8> {
+    let mut i = 0;
+    while i < system_timers.len() {
+        let name = system_timers[i].1.as_bytes();
+        let owner = owner.as_bytes();
+        if name.len() == owner.len() {
+            let mut j = 0;
+            while j < name.len() && name[j] == owner[j] {
+                j += 1;
+            }
+            if j == name.len() {
+                return Some(system_timers[i].0);
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns index of the system clock timer, if the port has one and [crate::init_system]
+/// has reserved and started it.
+pub(crate) fn clock_timer() -> Option<u8> {
+    CLOCK_TIMER.filter(|&timer_index| {
+        Port::timer_slot_state(timer_index) == TimerSlotState::Reserved(CLOCK_TIMER_OWNER)
+    })
+}
+
+/// Template for new ports, compiled only for tests.
+#[cfg(test)]
+mod minimal;
+
 /// Port is an alias of PortTrait implementation for a current platform
 
 #[cfg(any(target_arch = "riscv32", target_arch = "xtensa"))]
@@ -53,6 +235,21 @@ pub mod xtensa_esp32;
 mod arch {
     use super::xtensa_esp32;
     pub type Port = xtensa_esp32::XtensaEsp32;
+    /// Hardware timers used by Martos itself. Esp32 has only two timers, so the system clock
+    /// takes the second one, unless it is used by network. With network there is no system clock,
+    /// so timed sleeps of tasks are not available and scheduler does not record switch times.
+    pub const SYSTEM_TIMERS: &[(u8, &str)] = &[
+        #[cfg(feature = "preemptive")]
+        (0, "preemptive"),
+        #[cfg(feature = "network")]
+        (1, "network"),
+        #[cfg(not(feature = "network"))]
+        (1, super::CLOCK_TIMER_OWNER),
+    ];
+    const _: () = assert!(super::valid_system_timers(
+        SYSTEM_TIMERS,
+        xtensa_esp32::hardware_timer::TIMER_COUNT
+    ));
     #[cfg(feature = "preemptive")]
     pub type TrapFrame = xtensa_esp32::TrapFrame;
     #[cfg(feature = "preemptive")]
@@ -71,10 +268,16 @@ pub mod mok;
 mod arch {
     use super::mok;
     pub type Port = mok::Mok;
+    /// Hardware timers used by Martos itself. The last timer is the system clock.
+    pub const SYSTEM_TIMERS: &[(u8, &str)] = &[(4, super::CLOCK_TIMER_OWNER)];
+    const _: () = assert!(super::valid_system_timers(
+        SYSTEM_TIMERS,
+        mok::hardware_timer::TIMER_COUNT
+    ));
     #[cfg(feature = "preemptive")]
     pub type TrapFrame = mok::TrapFrame;
     #[cfg(feature = "preemptive")]
-    pub const STACK_ALIGN: usize = 0;
+    pub const STACK_ALIGN: usize = 16;
 }
 
 #[cfg(any(target_arch = "mips64", feature = "mips64_timer_tests"))]
@@ -83,6 +286,12 @@ pub mod mips64;
 mod arch {
     use super::mips64;
     pub type Port = mips64::Mips64;
+    /// Hardware timers used by Martos itself. The last timer is the system clock.
+    pub const SYSTEM_TIMERS: &[(u8, &str)] = &[(4, super::CLOCK_TIMER_OWNER)];
+    const _: () = assert!(super::valid_system_timers(
+        SYSTEM_TIMERS,
+        mips64::hardware_timer::TIMER_COUNT
+    ));
     #[cfg(feature = "preemptive")]
     pub type TrapFrame = ();
     #[cfg(feature = "preemptive")]
@@ -90,3 +299,7 @@ mod arch {
 }
 
 pub use arch::*;
+
+/// Index of the hardware timer, that is reserved as system clock, or None if the port has no timer for it.
+/// The clock timer is not available to the user.
+pub const CLOCK_TIMER: Option<u8> = find_system_timer(SYSTEM_TIMERS, CLOCK_TIMER_OWNER);
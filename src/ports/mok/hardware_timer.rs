@@ -1,10 +1,106 @@
+use crate::timer::{TimerError, TimerSlotState};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use core::time::Duration;
 
+// Declare timer_tests file as child file to test private functions.
+#[cfg(test)]
+#[path = "../../../tests/mok/timer_tests.rs"]
+mod mok_timer_tests;
+
+/// Number of hardware timers emulated by Mok.
+pub const TIMER_COUNT: u8 = 5;
+
+/// Indicators showing whether the timers are in use.
+static TIMERS_IN_USE: [AtomicBool; TIMER_COUNT as usize] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+
+/// Emulated CPU frequency.
+pub const CPU_FREQUENCY_HZ: u32 = 160_000_000;
+
+/// Emulated frequency of the clock, that drives the timers.
+pub const SOURCE_CLOCK_HZ: u32 = 80_000_000;
+
+/// Divider of the source clock, that gives 1 µs timer resolution.
+const DEFAULT_DIVIDER: u32 = SOURCE_CLOCK_HZ / 1_000_000;
+
+/// Virtual clock in nanoseconds. It is advanced only by delays, so host tests never sleep.
+static VIRTUAL_TIME_NS: AtomicU64 = AtomicU64::new(0);
+
+/// Indicators showing whether the timers are started.
+static TIMERS_STARTED: [AtomicBool; TIMER_COUNT as usize] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+
+/// Values of the virtual clock at the moments the timers were started.
+static TIMERS_START_NS: [AtomicU64; TIMER_COUNT as usize] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+/// Dividers of the source clock for the timers.
+static TIMERS_DIVIDER: [AtomicU32; TIMER_COUNT as usize] = [
+    AtomicU32::new(DEFAULT_DIVIDER),
+    AtomicU32::new(DEFAULT_DIVIDER),
+    AtomicU32::new(DEFAULT_DIVIDER),
+    AtomicU32::new(DEFAULT_DIVIDER),
+    AtomicU32::new(DEFAULT_DIVIDER),
+];
+
+/// Names of Martos subsystems owning reserved timers.
+static mut TIMER_OWNERS: [Option<&'static str>; TIMER_COUNT as usize] =
+    [None; TIMER_COUNT as usize];
+
 /// Mok hardware timer setup.
 pub fn setup_hardware_timer() {}
 
+/// Mok attempt to acquire timer.
+pub fn try_acquire_timer(timer_index: u8) -> bool {
+    TIMERS_IN_USE[timer_index as usize]
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_ok()
+}
+
+/// Mok reserve timer for Martos subsystem.
+pub fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+    if try_acquire_timer(timer_index) {
+        unsafe {
+            TIMER_OWNERS[timer_index as usize] = Some(owner);
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Mok getting state of timer.
+pub fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+    match unsafe { TIMER_OWNERS[timer_index as usize] } {
+        Some(owner) => TimerSlotState::Reserved(owner),
+        None if TIMERS_IN_USE[timer_index as usize].load(Ordering::Relaxed) => {
+            TimerSlotState::UserAcquired
+        }
+        None => TimerSlotState::Free,
+    }
+}
+
 /// Mok start harware timer.
-pub fn start_hardware_timer() {}
+pub fn start_hardware_timer(timer_index: u8) {
+    TIMERS_START_NS[timer_index as usize]
+        .store(VIRTUAL_TIME_NS.load(Ordering::Relaxed), Ordering::Relaxed);
+    TIMERS_STARTED[timer_index as usize].store(true, Ordering::Release);
+}
 
 /// Mok change operating mode of hardware timer.
 pub fn set_reload_mode(_auto_reload: bool) {}
@@ -13,9 +109,61 @@ pub fn set_reload_mode(_auto_reload: bool) {}
 pub fn change_period_timer(_period: Duration) {}
 
 /// Mok getting counter value of hardware timer.
-pub fn get_time() -> Duration {
-    Duration::new(0, 0)
+/// Returns virtual time passed since the timer start, rounded down to whole timer ticks.
+pub fn get_time(timer_index: u8) -> Duration {
+    if TIMERS_STARTED[timer_index as usize].load(Ordering::Acquire) {
+        let start_ns = TIMERS_START_NS[timer_index as usize].load(Ordering::Relaxed);
+        let elapsed_ns = (VIRTUAL_TIME_NS.load(Ordering::Relaxed) - start_ns) as u128;
+        // Tick length is divider / SOURCE_CLOCK_HZ seconds.
+        let tick_ns = timer_divider(timer_index) as u128 * 1_000_000_000;
+        let ticks = elapsed_ns * SOURCE_CLOCK_HZ as u128 / tick_ns;
+        Duration::from_nanos((ticks * tick_ns / SOURCE_CLOCK_HZ as u128) as u64)
+    } else {
+        Duration::new(0, 0)
+    }
+}
+
+/// Mok getting divider of the source clock for the timer.
+pub fn timer_divider(timer_index: u8) -> u32 {
+    TIMERS_DIVIDER[timer_index as usize].load(Ordering::Relaxed)
+}
+
+/// Mok change divider of the source clock for the timer. Any non-zero divider is supported.
+pub fn set_timer_divider(timer_index: u8, divider: u32) -> Result<(), TimerError> {
+    if divider == 0 {
+        return Err(TimerError::UnsupportedResolution);
+    }
+    if TIMERS_STARTED[timer_index as usize].load(Ordering::Acquire) {
+        return Err(TimerError::Running);
+    }
+    TIMERS_DIVIDER[timer_index as usize].store(divider, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Mok busy-wait delay. Advances the virtual clock instead of waiting.
+pub fn delay_us(us: u64) {
+    advance_virtual_time_ns(us.saturating_mul(1000));
+}
+
+/// Advances the virtual clock by the specified number of nanoseconds.
+pub(crate) fn advance_virtual_time_ns(ns: u64) {
+    VIRTUAL_TIME_NS.fetch_add(ns, Ordering::Relaxed);
 }
 
 /// Mok release hardware timer.
-pub fn release_hardware_timer() {}
+pub fn release_hardware_timer(timer_index: u8) {
+    TIMERS_STARTED[timer_index as usize].store(false, Ordering::Release);
+    TIMERS_DIVIDER[timer_index as usize].store(DEFAULT_DIVIDER, Ordering::Relaxed);
+    TIMERS_IN_USE[timer_index as usize].store(false, Ordering::Release);
+}
+
+/// Mok release all hardware timers, including reserved ones, and reset the virtual clock.
+pub fn reset_hardware_timers() {
+    for timer_index in 0..TIMER_COUNT {
+        release_hardware_timer(timer_index);
+        unsafe {
+            TIMER_OWNERS[timer_index as usize] = None;
+        }
+    }
+    VIRTUAL_TIME_NS.store(0, Ordering::Relaxed);
+}
@@ -1,31 +1,46 @@
 pub mod hardware_timer;
 pub mod memory_manager;
-#[cfg(feature = "network")]
-pub mod network;
 
-use crate::ports::PortTrait;
+use crate::ports::{HeapPort, PortTrait, TimerPort};
+use crate::timer::{TimerError, TimerSlotState};
 
 /// PortTrait implementation for Mok platform
 pub struct Mok;
-impl PortTrait for Mok {
+impl PortTrait for Mok {}
+
+impl HeapPort for Mok {
     fn init_heap() {
         memory_manager::init_heap();
     }
+}
 
+impl TimerPort for Mok {
     fn setup_hardware_timer() {
         hardware_timer::setup_hardware_timer();
     }
 
-    fn valid_timer_index(_timer_index: u8) -> bool {
-        true
+    fn timer_count() -> u8 {
+        hardware_timer::TIMER_COUNT
     }
 
-    fn try_acquire_timer(_timer_index: u8) -> bool {
-        true
+    fn valid_timer_index(timer_index: u8) -> bool {
+        timer_index < hardware_timer::TIMER_COUNT
+    }
+
+    fn try_acquire_timer(timer_index: u8) -> bool {
+        hardware_timer::try_acquire_timer(timer_index)
+    }
+
+    fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+        Self::valid_timer_index(timer_index) && hardware_timer::reserve_timer(timer_index, owner)
     }
 
-    fn start_hardware_timer(_timer_index: u8) {
-        hardware_timer::start_hardware_timer();
+    fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+        hardware_timer::timer_slot_state(timer_index)
+    }
+
+    fn start_hardware_timer(timer_index: u8) {
+        hardware_timer::start_hardware_timer(timer_index);
     }
 
     fn set_reload_mode(_timer_index: u8, auto_reload: bool) {
@@ -36,31 +51,48 @@ impl PortTrait for Mok {
         hardware_timer::change_period_timer(period);
     }
 
-    fn get_time(_timer_index: u8) -> core::time::Duration {
-        hardware_timer::get_time()
+    fn get_time(timer_index: u8) -> core::time::Duration {
+        hardware_timer::get_time(timer_index)
+    }
+
+    fn timer_source_clock_hz() -> u32 {
+        hardware_timer::SOURCE_CLOCK_HZ
+    }
+
+    fn timer_divider(timer_index: u8) -> u32 {
+        hardware_timer::timer_divider(timer_index)
+    }
+
+    fn set_timer_divider(timer_index: u8, divider: u32) -> Result<(), TimerError> {
+        hardware_timer::set_timer_divider(timer_index, divider)
     }
 
     fn stop_hardware_timer(_timer_index: u8) -> bool {
         false
     }
 
-    fn release_hardware_timer(_timer_index: u8) {
-        hardware_timer::release_hardware_timer()
+    fn release_hardware_timer(timer_index: u8) {
+        hardware_timer::release_hardware_timer(timer_index)
+    }
+
+    fn reset_hardware_timers() -> bool {
+        hardware_timer::reset_hardware_timers();
+        true
+    }
+
+    fn cpu_freq_hz() -> u32 {
+        hardware_timer::CPU_FREQUENCY_HZ
     }
 
-    #[cfg(feature = "network")]
-    fn init_network() {
-        network::init_network();
+    fn delay_us(us: u64) {
+        hardware_timer::delay_us(us);
     }
-    #[cfg(feature = "preemptive")]
-    fn setup_interrupt() {}
-    #[cfg(feature = "preemptive")]
-    fn setup_stack(thread: &mut crate::task_manager::preemptive::Thread) {}
-    #[cfg(feature = "preemptive")]
-    fn save_ctx(thread_ctx: &mut crate::ports::TrapFrame, isr_ctx: &crate::ports::TrapFrame) {}
-    #[cfg(feature = "preemptive")]
-    fn load_ctx(thread_ctx: &crate::ports::TrapFrame, isr_ctx: &mut crate::ports::TrapFrame) {}
 }
 
+#[cfg(feature = "network")]
+unsupported_network_port!(Mok);
+#[cfg(feature = "preemptive")]
+unsupported_preempt_port!(Mok);
+
 #[allow(dead_code)]
 pub type TrapFrame = ();
@@ -1,2 +0,0 @@
-/// Mok network initialization.
-pub fn init_network() {}
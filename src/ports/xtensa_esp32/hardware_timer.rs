@@ -1,5 +1,8 @@
-use core::sync::atomic::{AtomicBool, Ordering};
+use crate::timer::{TimerError, TimerSlotState};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use core::time::Duration;
+use esp_hal::clock::Clock;
+use esp_hal::delay::Delay;
 use esp_hal::timer::timg::{Timer, Timer0, TimerGroup};
 use esp_hal::{peripherals::*, prelude::*};
 
@@ -10,17 +13,134 @@ pub static mut PERIFERALS_RNG: Option<RNG> = None;
 pub static mut PERIFERALS_RADIO_CLK: Option<RADIO_CLK> = None;
 pub static mut PERIFERALS_WIFI: Option<WIFI> = None;
 
-static TIMER_BUSY: AtomicBool = AtomicBool::new(false);
+/// Number of hardware timers. Index 0 is timer 0 of timer group 0 ([TIMER00]),
+/// index 1 is timer 0 of timer group 1 ([TIMER10]). Timer 1 of the groups is not available on every chip,
+/// so it is not used.
+pub const TIMER_COUNT: u8 = 2;
+
+/// Frequency of APB clock, that drives the timer groups.
+pub const SOURCE_CLOCK_HZ: u32 = 80_000_000;
+
+/// Minimal divider of APB clock supported by the timer groups.
+const MIN_TIMER_DIVIDER: u32 = 2;
+
+/// Maximal divider of APB clock supported by the timer groups. It is written as 0 to the register.
+const MAX_TIMER_DIVIDER: u32 = 65536;
+
+/// Offset of configuration register of timer 0 in the timer group.
+const CONFIG_REG: usize = 0x00;
+/// Offset of the lower half of the latched counter of timer 0.
+const LO_REG: usize = 0x04;
+/// Offset of the higher half of the latched counter of timer 0.
+const HI_REG: usize = 0x08;
+/// Offset of the register, writing to which latches the counter of timer 0.
+const UPDATE_REG: usize = 0x0C;
+/// Offset of the lower half of the alarm value of timer 0.
+const ALARM_LO_REG: usize = 0x10;
+/// Offset of the higher half of the alarm value of timer 0.
+const ALARM_HI_REG: usize = 0x14;
+
+/// Enable bit of the configuration register.
+const CONFIG_EN: u32 = 1 << 31;
+/// Position of the divider field in the configuration register.
+const CONFIG_DIVIDER_SHIFT: u32 = 13;
+/// Mask of the divider field in the configuration register.
+const CONFIG_DIVIDER_MASK: u32 = 0xFFFF << CONFIG_DIVIDER_SHIFT;
+/// Update bit of the latch register. Newer chips clear it, when the counter is latched.
+const UPDATE_BIT: u32 = 1 << 31;
+
+/// Dividers of APB clock for the timers.
+static TIMERS_DIVIDER: [AtomicU32; TIMER_COUNT as usize] = [AtomicU32::new(0), AtomicU32::new(0)];
+
+/// Dividers of APB clock configured by esp-hal at setup. They are restored, when the timer is released.
+static DEFAULT_TIMERS_DIVIDER: [AtomicU32; TIMER_COUNT as usize] =
+    [AtomicU32::new(0), AtomicU32::new(0)];
+
+/// Indicators showing whether the timers are in use.
+static TIMERS_BUSY: [AtomicBool; TIMER_COUNT as usize] =
+    [AtomicBool::new(false), AtomicBool::new(false)];
+
+/// CPU frequency configured at hardware timer setup.
+static CPU_FREQUENCY_HZ: AtomicU32 = AtomicU32::new(0);
+
+/// Names of Martos subsystems owning reserved timers.
+static mut TIMER_OWNERS: [Option<&'static str>; TIMER_COUNT as usize] =
+    [None; TIMER_COUNT as usize];
+
+/// Calls the body with the hardware timer, that corresponds to the timer index.
+macro_rules! with_timer {
+    ($timer_index:expr, |$timer:ident| $body:expr) => {
+        unsafe {
+            match $timer_index {
+                0 => {
+                    let $timer = TIMER00.take().expect("Timer error");
+                    let result = $body;
+                    TIMER00 = Some($timer);
+                    result
+                }
+                _ => {
+                    let $timer = TIMER10.take().expect("Timer error");
+                    let result = $body;
+                    TIMER10 = Some($timer);
+                    result
+                }
+            }
+        }
+    };
+}
+
+/// Returns pointer to register of timer 0 of the timer group, that corresponds to the timer index.
+fn timer_register(timer_index: u8, offset: usize) -> *mut u32 {
+    let base = match timer_index {
+        0 => TIMG0::PTR as usize,
+        _ => TIMG1::PTR as usize,
+    };
+    (base + offset) as *mut u32
+}
+
+/// Reads register of the timer.
+fn read_register(timer_index: u8, offset: usize) -> u32 {
+    unsafe { core::ptr::read_volatile(timer_register(timer_index, offset)) }
+}
+
+/// Writes register of the timer.
+fn write_register(timer_index: u8, offset: usize, value: u32) {
+    unsafe { core::ptr::write_volatile(timer_register(timer_index, offset), value) }
+}
+
+/// Writes divider of APB clock to the configuration register of the timer.
+fn write_divider(timer_index: u8, divider: u32) {
+    let config = read_register(timer_index, CONFIG_REG) & !CONFIG_DIVIDER_MASK;
+    let field = (divider % MAX_TIMER_DIVIDER) << CONFIG_DIVIDER_SHIFT;
+    write_register(timer_index, CONFIG_REG, config | field);
+    TIMERS_DIVIDER[timer_index as usize].store(divider, Ordering::Relaxed);
+}
+
+/// Reads divider of APB clock from the configuration register of the timer.
+fn read_divider(timer_index: u8) -> u32 {
+    match (read_register(timer_index, CONFIG_REG) & CONFIG_DIVIDER_MASK) >> CONFIG_DIVIDER_SHIFT {
+        0 => MAX_TIMER_DIVIDER,
+        divider => divider.max(MIN_TIMER_DIVIDER),
+    }
+}
 
 /// Esp32 hardware timer setup.
 pub fn setup_hardware_timer() {
-    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let config = esp_hal::Config::default();
+    CPU_FREQUENCY_HZ.store(config.cpu_clock.hz(), Ordering::Relaxed);
+    let peripherals = esp_hal::init(config);
     let timer_group0 = TimerGroup::new(peripherals.TIMG0);
     let timer_group1 = TimerGroup::new(peripherals.TIMG1);
 
     let timer00 = timer_group0.timer0;
     let timer10 = timer_group1.timer0;
 
+    for timer_index in 0..TIMER_COUNT {
+        let divider = read_divider(timer_index);
+        DEFAULT_TIMERS_DIVIDER[timer_index as usize].store(divider, Ordering::Relaxed);
+        TIMERS_DIVIDER[timer_index as usize].store(divider, Ordering::Relaxed);
+    }
+
     unsafe {
         TIMER00 = Some(timer00);
         TIMER10 = Some(timer10);
@@ -31,33 +151,118 @@ pub fn setup_hardware_timer() {
 }
 
 /// Esp32 attempt to acquire timer.
-pub fn try_acquire_timer() -> bool {
-    match TIMER_BUSY.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed) {
+pub fn try_acquire_timer(timer_index: u8) -> bool {
+    match TIMERS_BUSY[timer_index as usize].compare_exchange(
+        false,
+        true,
+        Ordering::Acquire,
+        Ordering::Relaxed,
+    ) {
         Ok(_) => true,
         Err(_) => false,
     }
 }
 
+/// Esp32 reserve timer for Martos subsystem.
+pub fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+    if try_acquire_timer(timer_index) {
+        unsafe {
+            TIMER_OWNERS[timer_index as usize] = Some(owner);
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Esp32 getting state of timer.
+pub fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+    match unsafe { TIMER_OWNERS[timer_index as usize] } {
+        Some(owner) => TimerSlotState::Reserved(owner),
+        None if TIMERS_BUSY[timer_index as usize].load(Ordering::Relaxed) => {
+            TimerSlotState::UserAcquired
+        }
+        None => TimerSlotState::Free,
+    }
+}
+
 /// Esp32 start harware timer.
-pub fn start_hardware_timer() {}
+pub fn start_hardware_timer(timer_index: u8) {
+    with_timer!(timer_index, |timer| timer.start())
+}
+
+/// Esp32 stop hardware timer.
+pub fn stop_hardware_timer(timer_index: u8) -> bool {
+    with_timer!(timer_index, |timer| timer.stop());
+    true
+}
 
 /// Esp32 change operating mode of hardware timer.
-pub fn set_reload_mode(_auto_reload: bool) {}
+pub fn set_reload_mode(timer_index: u8, auto_reload: bool) {
+    with_timer!(timer_index, |timer| timer.enable_auto_reload(auto_reload))
+}
 
-/// Esp32 change the period of hardware timer.
-pub fn change_period_timer(_period: Duration) {}
+/// Esp32 change the period of hardware timer. The period is converted to ticks of the configured divider,
+/// the alarm value of 54-bit counter is truncated to 64 bits.
+pub fn change_period_timer(timer_index: u8, period: Duration) {
+    let tick_ns = timer_divider(timer_index) as u128 * 1_000_000_000;
+    let ticks =
+        (period.as_nanos() * SOURCE_CLOCK_HZ as u128 / tick_ns).min(u64::MAX as u128) as u64;
+    write_register(timer_index, ALARM_LO_REG, ticks as u32);
+    write_register(timer_index, ALARM_HI_REG, (ticks >> 32) as u32);
+}
 
 /// Esp32 getting counter value of hardware timer.
-pub fn get_time() -> Duration {
-    unsafe {
-        let timer00 = TIMER00.take().expect("Timer error");
-        let tick_counter = timer00.now();
-        TIMER00 = Some(timer00);
-        Duration::from_micros(tick_counter.ticks())
+/// The counter is latched and scaled by the configured divider, time is rounded down to whole ticks.
+pub fn get_time(timer_index: u8) -> Duration {
+    write_register(timer_index, UPDATE_REG, UPDATE_BIT);
+    while read_register(timer_index, UPDATE_REG) & UPDATE_BIT != 0 {}
+    let ticks = (read_register(timer_index, HI_REG) as u64) << 32
+        | read_register(timer_index, LO_REG) as u64;
+    let tick_ns = timer_divider(timer_index) as u128 * 1_000_000_000;
+    Duration::from_nanos((ticks as u128 * tick_ns / SOURCE_CLOCK_HZ as u128) as u64)
+}
+
+/// Esp32 getting divider of APB clock for the timer.
+pub fn timer_divider(timer_index: u8) -> u32 {
+    TIMERS_DIVIDER[timer_index as usize].load(Ordering::Relaxed)
+}
+
+/// Esp32 change divider of APB clock for the timer. Dividers from 2 to 65536 are supported.
+/// Divider of the started timer can not be changed.
+pub fn set_timer_divider(timer_index: u8, divider: u32) -> Result<(), TimerError> {
+    if !(MIN_TIMER_DIVIDER..=MAX_TIMER_DIVIDER).contains(&divider) {
+        return Err(TimerError::UnsupportedResolution);
     }
+    if read_register(timer_index, CONFIG_REG) & CONFIG_EN != 0 {
+        return Err(TimerError::Running);
+    }
+    write_divider(timer_index, divider);
+    Ok(())
+}
+
+/// Esp32 release hardware timer. The timer is stopped and its divider is restored.
+pub fn release_hardware_timer(timer_index: u8) {
+    stop_hardware_timer(timer_index);
+    write_divider(
+        timer_index,
+        DEFAULT_TIMERS_DIVIDER[timer_index as usize].load(Ordering::Relaxed),
+    );
+    TIMERS_BUSY[timer_index as usize].store(false, Ordering::Release);
 }
 
-/// Esp32 release hardware timer.
-pub fn release_hardware_timer() {
-    TIMER_BUSY.store(false, Ordering::Release);
+/// Esp32 getting CPU frequency.
+pub fn cpu_freq_hz() -> u32 {
+    CPU_FREQUENCY_HZ.load(Ordering::Relaxed)
+}
+
+/// Esp32 busy-wait delay. Uses esp-hal delay calibrated by the clock configuration.
+pub fn delay_us(us: u64) {
+    let delay = Delay::new();
+    let mut remaining_us = us;
+    while remaining_us > 0 {
+        let chunk_us = remaining_us.min(u32::MAX as u64);
+        delay.delay_micros(chunk_us as u32);
+        remaining_us -= chunk_us;
+    }
 }
@@ -5,77 +5,122 @@ pub mod network;
 #[cfg(feature = "preemptive")]
 mod preempt;
 
-use crate::ports::PortTrait;
+#[cfg(feature = "network")]
+use crate::ports::NetworkPort;
+#[cfg(feature = "preemptive")]
+use crate::ports::PreemptPort;
+use crate::ports::{HeapPort, PortTrait, TimerPort};
+use crate::timer::{TimerError, TimerSlotState};
 #[cfg(feature = "network")]
 use esp_wifi::esp_now::EspNow;
 
 // TODO: make it port just for esp32, not only for XtensaEsp32
 /// PortTrait implementation for XtensaEsp32 platform
 pub struct XtensaEsp32;
-impl PortTrait for XtensaEsp32 {
+impl PortTrait for XtensaEsp32 {}
+
+impl TimerPort for XtensaEsp32 {
     fn setup_hardware_timer() {
         hardware_timer::setup_hardware_timer();
     }
 
-    fn valid_timer_index(_timer_index: u8) -> bool {
-        true
+    fn timer_count() -> u8 {
+        hardware_timer::TIMER_COUNT
+    }
+
+    fn valid_timer_index(timer_index: u8) -> bool {
+        timer_index < hardware_timer::TIMER_COUNT
+    }
+
+    fn try_acquire_timer(timer_index: u8) -> bool {
+        hardware_timer::try_acquire_timer(timer_index)
+    }
+
+    fn reserve_timer(timer_index: u8, owner: &'static str) -> bool {
+        Self::valid_timer_index(timer_index) && hardware_timer::reserve_timer(timer_index, owner)
+    }
+
+    fn timer_slot_state(timer_index: u8) -> TimerSlotState {
+        hardware_timer::timer_slot_state(timer_index)
+    }
+
+    fn start_hardware_timer(timer_index: u8) {
+        hardware_timer::start_hardware_timer(timer_index);
     }
 
-    fn try_acquire_timer(_timer_index: u8) -> bool {
-        hardware_timer::try_acquire_timer()
+    fn set_reload_mode(timer_index: u8, auto_reload: bool) {
+        hardware_timer::set_reload_mode(timer_index, auto_reload);
     }
 
-    fn start_hardware_timer(_timer_index: u8) {
-        hardware_timer::start_hardware_timer();
+    fn change_period_timer(timer_index: u8, period: core::time::Duration) {
+        hardware_timer::change_period_timer(timer_index, period);
     }
 
-    fn set_reload_mode(_timer_index: u8, auto_reload: bool) {
-        hardware_timer::set_reload_mode(auto_reload);
+    fn get_time(timer_index: u8) -> core::time::Duration {
+        hardware_timer::get_time(timer_index)
     }
 
-    fn change_period_timer(_timer_index: u8, period: core::time::Duration) {
-        hardware_timer::change_period_timer(period);
+    fn timer_source_clock_hz() -> u32 {
+        hardware_timer::SOURCE_CLOCK_HZ
     }
 
-    fn get_time(_timer_index: u8) -> core::time::Duration {
-        hardware_timer::get_time()
+    fn timer_divider(timer_index: u8) -> u32 {
+        hardware_timer::timer_divider(timer_index)
     }
 
-    fn stop_hardware_timer(_timer_index: u8) -> bool {
+    fn set_timer_divider(timer_index: u8, divider: u32) -> Result<(), TimerError> {
+        hardware_timer::set_timer_divider(timer_index, divider)
+    }
+
+    fn stop_hardware_timer(timer_index: u8) -> bool {
+        hardware_timer::stop_hardware_timer(timer_index)
+    }
+
+    fn release_hardware_timer(timer_index: u8) {
+        hardware_timer::release_hardware_timer(timer_index)
+    }
+
+    fn reset_hardware_timers() -> bool {
         false
     }
 
-    fn release_hardware_timer(_timer_index: u8) {
-        hardware_timer::release_hardware_timer()
+    fn cpu_freq_hz() -> u32 {
+        hardware_timer::cpu_freq_hz()
     }
 
+    fn delay_us(us: u64) {
+        hardware_timer::delay_us(us);
+    }
+}
+
+impl HeapPort for XtensaEsp32 {
     fn init_heap() {
         memory_manager::init_heap();
     }
+}
 
-    #[cfg(feature = "network")]
+#[cfg(feature = "network")]
+impl NetworkPort for XtensaEsp32 {
     fn init_network() {
         network::init_network();
     }
 
-    #[cfg(feature = "network")]
     fn get_esp_now() -> EspNow<'static> {
         network::get_esp_now()
     }
+}
 
-    #[cfg(feature = "preemptive")]
+#[cfg(feature = "preemptive")]
+impl PreemptPort for XtensaEsp32 {
     fn setup_interrupt() {
         preempt::setup_interrupt();
     }
-    #[cfg(feature = "preemptive")]
     fn setup_stack(thread: &mut crate::task_manager::preemptive::Thread) {
         preempt::setup_stack(thread);
     }
-    #[cfg(feature = "preemptive")]
     fn save_ctx(thread_ctx: &mut TrapFrame, isr_ctx: &TrapFrame) {
         preempt::save_ctx(thread_ctx, isr_ctx)
     }
-    #[cfg(feature = "preemptive")]
     fn load_ctx(thread_ctx: &TrapFrame, isr_ctx: &mut TrapFrame) {
         preempt::load_ctx(thread_ctx, isr_ctx)
     }
@@ -53,7 +53,7 @@ mod context_switch {
         thread.context.A7 = thread.task.loop_fn as u32; // A3
         thread.context.A8 = thread.task.stop_condition_fn as u32; // A4
 
-        let stack_ptr = thread.stack as usize + crate::task_manager::preemptive::THREAD_STACK_SIZE;
+        let stack_ptr = thread.stack as usize + thread.stack_layout.size();
         thread.context.A1 = stack_ptr as u32;
 
         thread.context.PS = 0x00040000 | (1 & 3) << 16;
@@ -1,61 +1,468 @@
 extern crate alloc;
 
+use crate::ports::{Port, TimerPort};
 use crate::task_manager::{
     task::{Task, TaskLoopFunctionType, TaskSetupFunctionType, TaskStopConditionFunctionType},
-    TaskManagerTrait, TASK_MANAGER,
+    CancelToken, InboxError, InboxMessage, IterationOutcome, TaskError, TaskFlags, TaskIdType,
+    TaskInfo, TaskManagerTrait, TaskStatus, TASK_NAME_MAX_LEN,
 };
+use alloc::collections::VecDeque;
 use alloc::vec::Vec;
-use core::task::{Poll, RawWaker, RawWakerVTable, Waker};
+use core::cell::RefCell;
+use core::fmt::{self, Write};
+use core::task::Poll;
+use core::time::Duration;
 use core::{future::Future, pin::Pin, task::Context};
+use critical_section::Mutex;
+
+// Declare cooperative_tests file as child file to test private functions.
+#[cfg(test)]
+#[path = "../../tests/cooperative/cooperative_tests.rs"]
+mod cooperative_tests;
 
 /// The number of tasks can fit into a type usize.
 pub type TaskNumberType = usize;
+
+/// State of task manager. It is accessed only through [with_task_manager].
+static TASK_MANAGER: Mutex<RefCell<CooperativeTaskManager>> =
+    Mutex::new(RefCell::new(CooperativeTaskManager::new()));
+
+/// Calls the function with exclusive reference to task manager inside of critical section.
+/// Task functions and hooks must not be called from the function: they may use task manager too,
+/// and nested access panics.
+fn with_task_manager<R>(f: impl FnOnce(&mut CooperativeTaskManager) -> R) -> R {
+    critical_section::with(|cs| f(&mut TASK_MANAGER.borrow_ref_mut(cs)))
+}
+
+/// Maximum sleep duration in microseconds. Longer durations are truncated,
+/// so deadlines can be compared with wrapping arithmetic.
+const MAX_SLEEP_US: u64 = i64::MAX as u64;
+
+/// Returns time in microseconds until the deadline or None if the deadline is reached.
+/// Clock values are compared with wrapping arithmetic, so deadlines survive wrapping of the clock.
+fn time_until(deadline_us: u64, now_us: u64) -> Option<u64> {
+    let remaining_us = deadline_us.wrapping_sub(now_us) as i64;
+    if remaining_us > 0 {
+        Some(remaining_us as u64)
+    } else {
+        None
+    }
+}
+
+/// Returns time of the system clock in microseconds or None if the port has no clock
+/// or the system is not initialized.
+fn clock_us() -> Option<u64> {
+    crate::ports::clock_timer().map(|timer_index| Port::get_time(timer_index).as_micros() as u64)
+}
+
+/// Policy of task restarting after its stop condition is reached.
+/// By default setup function is called again on every restart, it can be skipped with
+/// [CooperativeTaskManager::set_restart_setup]. Deleted or terminated task is never restarted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Task is never restarted.
+    Never,
+    /// Task is restarted every time its stop condition is reached.
+    Always,
+    /// Task is restarted at most the specified number of times.
+    MaxTimes(u32),
+}
+
+/// State of waiting for termination of a task, that is returned by [CooperativeTaskManager::try_wait_for_termination].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinHandleState {
+    /// The calling task sleeps until the task is completed, terminated or deleted.
+    Waiting,
+    /// The task is already completed or there is no such task, so the calling task is not put to sleep.
+    AlreadyTerminated,
+}
+
+/// Reason, why a sleeping task waits, in [DeadlockReport].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitReason {
+    /// Task sleeps until it is woken up by identifier, e.g. by another task or an interrupt handler.
+    WakeUp,
+    /// Task sleeps until the deadline of the system clock.
+    Deadline,
+    /// Task waits for termination of the task with the specified identifier.
+    Join(TaskIdType),
+}
+
+/// Report of deadlock: cycle of tasks, where every task waits for termination of the next one
+/// and the last task waits for the first one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadlockReport {
+    /// Identifiers of tasks in the cycle and reasons, why they wait.
+    pub tasks: Vec<(TaskIdType, WaitReason)>,
+}
+
+/// Type of hook, that is called when deadlock of tasks is detected.
+pub type DeadlockHookType = fn(&DeadlockReport);
+
+/// Name of a task. Names from Rust are stored as references without copying,
+/// names from C API are copied into a fixed buffer.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TaskName {
+    /// Name with static lifetime.
+    Static(&'static str),
+    /// Name copied into a fixed buffer. Contains the buffer and the length of the name.
+    Inline([u8; TASK_NAME_MAX_LEN], u8),
+}
+
+impl TaskName {
+    /// Creates name from bytes, truncating it to [TASK_NAME_MAX_LEN] bytes on character boundary.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> TaskName {
+        let bytes = &bytes[..bytes.len().min(TASK_NAME_MAX_LEN)];
+        let len = match core::str::from_utf8(bytes) {
+            Ok(name) => name.len(),
+            Err(error) => error.valid_up_to(),
+        };
+        let mut buffer = [0; TASK_NAME_MAX_LEN];
+        buffer[..len].copy_from_slice(&bytes[..len]);
+        TaskName::Inline(buffer, len as u8)
+    }
+
+    /// Returns name as fixed buffer padded with zeros, truncating it like [TaskName::from_bytes].
+    fn to_buffer(self) -> [u8; TASK_NAME_MAX_LEN] {
+        match self {
+            TaskName::Static(name) => TaskName::from_bytes(name.as_bytes()).to_buffer(),
+            TaskName::Inline(buffer, _) => buffer,
+        }
+    }
+
+    /// Returns name as string slice.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            TaskName::Static(name) => name,
+            TaskName::Inline(buffer, len) => {
+                core::str::from_utf8(&buffer[..*len as usize]).unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// Reason of task exit, that is passed to the exit hook of the task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitReason {
+    /// Stop condition of the task is reached and the task will not be executed anymore.
+    StopCondition,
+    /// Stop condition of the task is reached and the task is restarted. Contains the number of restarts.
+    Restarted(u32),
+    /// Parent of the task is completed, so the task will not be executed anymore.
+    ParentFinished,
+    /// Task is terminated by [TaskManagerTrait::terminate_task] and removed from task manager.
+    Terminated,
+}
+
+impl ExitReason {
+    /// Returns integer code of the reason for C API:
+    /// 0 for stop condition, n > 0 if the task is restarted for the n-th time, -1 if parent is completed,
+    /// -2 if the task is terminated.
+    pub fn code(&self) -> i32 {
+        match self {
+            ExitReason::StopCondition => 0,
+            ExitReason::Restarted(count) => *count as i32,
+            ExitReason::ParentFinished => -1,
+            ExitReason::Terminated => -2,
+        }
+    }
+}
+
+#[cfg(not(feature = "c-library"))]
+/// Type of exit hook, that is called when stop condition of task is reached.
+pub type TaskExitHookType = fn(TaskIdType, ExitReason);
+#[cfg(feature = "c-library")]
+/// Type of exit hook, that is called when stop condition of task is reached.
+/// Second argument is the code of exit reason (see [ExitReason::code]).
+pub type TaskExitHookType = extern "C" fn(TaskIdType, i32);
+
+#[cfg(not(feature = "c-library"))]
+/// Type of hook, that is called before every iteration of task manager with identifier of the task.
+pub type TaskPreIterationHookType = fn(TaskIdType);
+#[cfg(feature = "c-library")]
+/// Type of hook, that is called before every iteration of task manager with identifier of the task.
+pub type TaskPreIterationHookType = extern "C" fn(TaskIdType);
+#[cfg(not(feature = "c-library"))]
+/// Type of hook, that is called after every iteration of task manager with identifier of the task
+/// and indicator whether loop function of the task was called.
+pub type TaskPostIterationHookType = fn(TaskIdType, bool);
+#[cfg(feature = "c-library")]
+/// Type of hook, that is called after every iteration of task manager with identifier of the task
+/// and indicator whether loop function of the task was called.
+pub type TaskPostIterationHookType = extern "C" fn(TaskIdType, bool);
+
+/// Specification of a task for adding tasks in batch.
+#[derive(Clone, Copy)]
+pub struct TaskSpec {
+    /// Setup function, that is called once at the beginning of task.
+    pub setup_fn: TaskSetupFunctionType,
+    /// Loop function, that is called in loop.
+    pub loop_fn: TaskLoopFunctionType,
+    /// Condition function for stopping loop function execution.
+    pub stop_condition_fn: TaskStopConditionFunctionType,
+}
+
+/// Bounded inbox of a task.
+pub(crate) struct Inbox {
+    /// Received messages in order of sending.
+    messages: VecDeque<InboxMessage>,
+    /// Maximum number of messages in the inbox.
+    capacity: usize,
+    /// Number of messages dropped because the inbox was full.
+    dropped: u32,
+}
+
 #[repr(C)]
 /// Future shell for task for cooperative execution.
 pub struct FutureTask {
+    /// Task identifier.
+    pub(crate) id: TaskIdType,
     /// Task to execute in task manager.
     pub(crate) task: Task,
     /// Marker for setup function completion.
     pub(crate) is_setup_completed: bool,
+    /// Policy of task restarting after its stop condition is reached.
+    pub(crate) restart_policy: RestartPolicy,
+    /// Number of times the task has been restarted.
+    pub(crate) restart_count: u32,
+    /// Indicator whether setup function is called again when the task is restarted.
+    pub(crate) rerun_setup: bool,
+    /// Function, that is called when stop condition of the task is reached.
+    pub(crate) exit_hook: Option<TaskExitHookType>,
+    /// Marker for task completion. Completed task is not executed anymore.
+    pub(crate) is_finished: bool,
+    /// Scheduling flags of the task.
+    pub(crate) flags: TaskFlags,
+    /// Number of consecutive loop iterations for [TaskFlags::EXCLUSIVE_BURST].
+    pub(crate) burst_iterations: u32,
+    /// Name of the task, if it is set.
+    pub(crate) name: Option<TaskName>,
+    /// Inbox of the task, if it is created.
+    pub(crate) inbox: Option<Inbox>,
+    /// Identifier of the parent task. The task is completed when its parent is completed.
+    pub(crate) parent: Option<TaskIdType>,
+    /// Marker for sleeping task. Sleeping task is not executed until it is woken up.
+    pub(crate) is_sleeping: bool,
+    /// Time of task manager clock in microseconds, when sleeping task is woken up.
+    /// None if the task sleeps until it is woken up explicitly.
+    pub(crate) wake_at: Option<u64>,
+    /// Marker for terminated task, that should be removed after its current iteration.
+    pub(crate) is_terminated: bool,
+    /// Number of task manager step and time of the clock in microseconds, when the task was woken up.
+    /// It is reset, when the task runs after waking up.
+    pub(crate) woken_at: Option<(u64, Option<u64>)>,
+    /// Number of task manager steps between the last wake up of the task and its next run.
+    pub(crate) wake_latency_iterations: u32,
+    /// Time in microseconds between the last wake up of the task and its next run.
+    pub(crate) wake_latency_us: u64,
+    /// Identifiers of tasks, that sleep until the task is completed.
+    pub(crate) joiners: Vec<TaskIdType>,
+    /// Identifier of the task, whose termination the sleeping task waits for.
+    pub(crate) waits_for: Option<TaskIdType>,
+    /// Marker for task, that runs ahead of other tasks once because of an event.
+    pub(crate) is_boosted: bool,
+    /// Number of events, that made the task run ahead of other tasks.
+    pub(crate) boosted_wakes: u32,
+    /// Token, that is cancelled when the task is terminated.
+    pub(crate) cancel_token: CancelToken,
 }
 
-impl Future for FutureTask {
-    type Output = ();
+/// Read-only view of task state. It borrows the task, so reading statistics does not allocate.
+pub struct TaskInfoRef<'a> {
+    /// Task to read state from.
+    future_task: &'a FutureTask,
+}
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut array: [usize; 8] = core::array::from_fn(|i| i);
-        array[0] = 5;
+impl TaskInfoRef<'_> {
+    /// Returns identifier of the task.
+    pub fn id(&self) -> TaskIdType {
+        self.future_task.id
+    }
+
+    /// Returns name of the task, if it is set.
+    pub fn name(&self) -> Option<&str> {
+        self.future_task.name.as_ref().map(TaskName::as_str)
+    }
+
+    /// Returns whether setup function of the task is completed.
+    pub fn is_setup_completed(&self) -> bool {
+        self.future_task.is_setup_completed
+    }
+
+    /// Returns whether the task is completed.
+    pub fn is_finished(&self) -> bool {
+        self.future_task.is_finished
+    }
+
+    /// Returns whether the task is sleeping.
+    pub fn is_sleeping(&self) -> bool {
+        self.future_task.is_sleeping
+    }
+
+    /// Returns number of times the task has been restarted.
+    pub fn restart_count(&self) -> u32 {
+        self.future_task.restart_count
+    }
+
+    /// Returns identifier of the parent task, if the task has parent.
+    pub fn parent(&self) -> Option<TaskIdType> {
+        self.future_task.parent
+    }
+
+    /// Returns number of messages in inbox, its capacity and number of dropped messages,
+    /// if the task has inbox.
+    pub fn inbox_stats(&self) -> Option<(usize, usize, u32)> {
+        self.future_task
+            .inbox
+            .as_ref()
+            .map(|inbox| (inbox.messages.len(), inbox.capacity, inbox.dropped))
+    }
+}
+
+/// Copy of task state, that is needed to execute one iteration of the task.
+/// Task functions are called through the copy, so no references to task manager are held
+/// while they run and they may safely add tasks.
+#[derive(Clone, Copy)]
+struct TaskIteration {
+    /// Task identifier.
+    id: TaskIdType,
+    /// Functions of the task.
+    task: Task,
+    /// Marker for setup function completion.
+    is_setup_completed: bool,
+    /// Marker for task completion.
+    is_finished: bool,
+    /// Marker for sleeping task.
+    is_sleeping: bool,
+    /// Indicator whether the restart policy allows one more restart of the task.
+    can_restart: bool,
+    /// Indicator whether setup function is called again when the task is restarted.
+    rerun_setup: bool,
+    /// Number of times the task has been restarted.
+    restart_count: u32,
+    /// Function, that is called when stop condition of the task is reached.
+    exit_hook: Option<TaskExitHookType>,
+}
+
+/// State change of task after one iteration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TaskTransition {
+    /// Setup function is completed.
+    SetupCompleted,
+    /// Loop function is completed.
+    LoopCompleted,
+    /// Stop condition is reached and the task is restarted.
+    Restarted,
+    /// Stop condition is reached and the task is completed.
+    Finished,
+    /// Task is sleeping, so none of its functions is called.
+    Skipped,
+}
+
+impl TaskIteration {
+    /// Executes one iteration of the task and returns state change, that should be applied to the task.
+    fn run(self) -> TaskTransition {
+        if self.is_finished {
+            return TaskTransition::Finished;
+        }
+        if self.is_sleeping {
+            return TaskTransition::Skipped;
+        }
         if (self.task.stop_condition_fn)() {
-            Poll::Ready(())
-        } else {
-            if !self.is_setup_completed {
-                (self.task.setup_fn)();
-                self.is_setup_completed = true;
+            if self.can_restart {
+                // New lifetime of the task starts with its setup function.
+                self.notify_exit(ExitReason::Restarted(self.restart_count + 1));
+                if self.rerun_setup {
+                    (self.task.setup_fn)();
+                }
+                TaskTransition::Restarted
             } else {
-                (self.task.loop_fn)();
+                self.notify_exit(ExitReason::StopCondition);
+                TaskTransition::Finished
             }
-            Poll::Pending
+        } else if !self.is_setup_completed {
+            (self.task.setup_fn)();
+            TaskTransition::SetupCompleted
+        } else {
+            (self.task.loop_fn)();
+            TaskTransition::LoopCompleted
+        }
+    }
+
+    /// Calls exit hook of the task, if it is set.
+    fn notify_exit(&self, reason: ExitReason) {
+        if let Some(exit_hook) = self.exit_hook {
+            #[cfg(not(feature = "c-library"))]
+            exit_hook(self.id, reason);
+            #[cfg(feature = "c-library")]
+            exit_hook(self.id, reason.code());
         }
     }
 }
 
-/// Creates simple task waker. May be more difficult in perspective.
-pub fn task_waker() -> Waker {
-    fn raw_clone(_: *const ()) -> RawWaker {
-        RawWaker::new(core::ptr::null::<()>(), &NOOP_WAKER_VTABLE)
+impl FutureTask {
+    /// Checks if the restart policy allows one more restart of the task.
+    fn can_restart(&self) -> bool {
+        match self.restart_policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Always => true,
+            RestartPolicy::MaxTimes(times) => self.restart_count < times,
+        }
     }
 
-    fn raw_wake(_: *const ()) {}
+    /// Returns number of consecutive loop iterations of the task before switching to the next task.
+    /// Returns 0 if the task does not run in bursts, so task manager switches after every poll.
+    fn burst_length(&self) -> u32 {
+        if self.flags.contains(TaskFlags::EXCLUSIVE_BURST) {
+            self.burst_iterations.max(1)
+        } else {
+            0
+        }
+    }
 
-    fn raw_wake_by_ref(_: *const ()) {}
+    /// Returns copy of task state for executing one iteration.
+    fn iteration(&self) -> TaskIteration {
+        TaskIteration {
+            id: self.id,
+            task: self.task,
+            is_setup_completed: self.is_setup_completed,
+            is_finished: self.is_finished,
+            is_sleeping: self.is_sleeping,
+            can_restart: self.can_restart(),
+            rerun_setup: self.rerun_setup,
+            restart_count: self.restart_count,
+            exit_hook: self.exit_hook,
+        }
+    }
 
-    fn raw_drop(_: *const ()) {}
+    /// Applies state change after iteration of the task.
+    fn apply(&mut self, transition: TaskTransition) -> Poll<()> {
+        match transition {
+            TaskTransition::SetupCompleted | TaskTransition::LoopCompleted => {
+                self.is_setup_completed = true;
+                Poll::Pending
+            }
+            TaskTransition::Restarted => {
+                self.restart_count += 1;
+                self.is_setup_completed = true;
+                Poll::Pending
+            }
+            TaskTransition::Finished => {
+                self.is_finished = true;
+                Poll::Ready(())
+            }
+            TaskTransition::Skipped => Poll::Pending,
+        }
+    }
+}
 
-    static NOOP_WAKER_VTABLE: RawWakerVTable =
-        RawWakerVTable::new(raw_clone, raw_wake, raw_wake_by_ref, raw_drop);
+impl Future for FutureTask {
+    type Output = ();
 
-    let raw_waker = RawWaker::new(core::ptr::null::<()>(), &NOOP_WAKER_VTABLE);
-    unsafe { Waker::from_raw(raw_waker) }
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let transition = self.iteration().run();
+        self.apply(transition)
+    }
 }
 
 #[repr(C)]
@@ -65,26 +472,87 @@ pub struct CooperativeTaskManager {
     pub(crate) tasks: Vec<FutureTask>,
     /// Index of task, that should be executed.
     pub(crate) task_to_execute_index: TaskNumberType,
+    /// Identifier, that is assigned to the next added task.
+    pub(crate) next_task_id: TaskIdType,
+    /// Number of consecutive loop iterations of the task, that is being executed.
+    pub(crate) burst_count: u32,
+    /// Hook, that is called before every iteration.
+    pub(crate) pre_iteration_hook: Option<TaskPreIterationHookType>,
+    /// Hook, that is called after every iteration.
+    pub(crate) post_iteration_hook: Option<TaskPostIterationHookType>,
+    /// Identifier of the task, whose functions are being executed.
+    pub(crate) current_task_id: Option<TaskIdType>,
+    /// The first name, that was given to several tasks. Only the first duplicate is recorded,
+    /// so the diagnostics dump warns about duplicates once.
+    pub(crate) duplicate_name: Option<TaskName>,
+    /// Number of task manager steps.
+    pub(crate) step_count: u64,
+    /// Identifiers of tasks woken up with [CooperativeTaskManager::try_wake_up_task_prioritized],
+    /// that are executed before the next task in round-robin order.
+    pub(crate) front_queue: VecDeque<TaskIdType>,
+    /// Identifier of the task, that continues round-robin order after tasks of the front queue.
+    pub(crate) resume_task_id: Option<TaskIdType>,
+    /// Hook, that is called when deadlock of tasks is detected.
+    pub(crate) deadlock_hook: Option<DeadlockHookType>,
+    /// Indicator whether the current deadlock is already reported.
+    pub(crate) is_deadlock_reported: bool,
 }
 
 impl TaskManagerTrait for CooperativeTaskManager {
-    fn add_task(
+    fn add_task_with_id(
         setup_fn: TaskSetupFunctionType,
         loop_fn: TaskLoopFunctionType,
         stop_condition_fn: TaskStopConditionFunctionType,
-    ) {
-        let task = Task {
-            setup_fn,
-            loop_fn,
-            stop_condition_fn,
-        };
-        let future_task = FutureTask {
-            task,
-            is_setup_completed: false,
-        };
-        unsafe {
-            TASK_MANAGER.tasks.push(future_task);
-        }
+    ) -> TaskIdType {
+        Self::add_restartable_task(setup_fn, loop_fn, stop_condition_fn, RestartPolicy::Never)
+    }
+
+    fn terminate_task(id: TaskIdType) -> Result<(), TaskError> {
+        let iteration = with_task_manager(|task_manager| {
+            let index = task_manager.position(id).ok_or(TaskError::NotFound)?;
+            let future_task = &mut task_manager.tasks[index];
+            if future_task.is_finished {
+                return Err(TaskError::InvalidState);
+            }
+            future_task.cancel_token.cancel();
+            future_task.is_finished = true;
+            future_task.is_terminated = true;
+            let iteration = future_task.iteration();
+            task_manager.wake_joiners(index);
+            Ok(iteration)
+        })?;
+        // Exit hook may add tasks, so it is called outside of critical section.
+        iteration.notify_exit(ExitReason::Terminated);
+        Self::finish_descendants(id);
+
+        with_task_manager(|task_manager| {
+            // Task, that is being executed, is removed by task manager after its iteration.
+            if task_manager.current_task_id != Some(id) {
+                if let Some(index) = task_manager.position(id) {
+                    task_manager.remove_task(index);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn terminate_current_task() -> Result<(), TaskError> {
+        let id = with_task_manager(|task_manager| task_manager.current_task_id)
+            .ok_or(TaskError::NotFound)?;
+        Self::terminate_task(id)
+    }
+
+    fn current_task_id() -> Option<TaskIdType> {
+        with_task_manager(|task_manager| task_manager.current_task_id)
+    }
+
+    fn current_cancel_token() -> Option<CancelToken> {
+        with_task_manager(|task_manager| {
+            let id = task_manager.current_task_id?;
+            task_manager
+                .find_task(id)
+                .map(|future_task| future_task.cancel_token.clone())
+        })
     }
 
     fn start_task_manager() -> ! {
@@ -100,30 +568,976 @@ impl CooperativeTaskManager {
         CooperativeTaskManager {
             tasks: Vec::new(),
             task_to_execute_index: 0,
+            next_task_id: 1,
+            burst_count: 0,
+            pre_iteration_hook: None,
+            post_iteration_hook: None,
+            current_task_id: None,
+            duplicate_name: None,
+            step_count: 0,
+            front_queue: VecDeque::new(),
+            resume_task_id: None,
+            deadlock_hook: None,
+            is_deadlock_reported: false,
+        }
+    }
+
+    /// Removes all tasks and hooks, so task manager starts from a clean state.
+    pub(crate) fn reset() {
+        with_task_manager(|task_manager| *task_manager = Self::new());
+    }
+
+    /// Add task to task manager with the specified restart policy. Returns identifier of the task.
+    /// When stop condition of the task is reached and the policy allows restart,
+    /// setup function is called again and the task continues with its loop function.
+    pub fn add_restartable_task(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        restart_policy: RestartPolicy,
+    ) -> TaskIdType {
+        let future_task = Self::create_task(setup_fn, loop_fn, stop_condition_fn, restart_policy);
+        let id = future_task.id;
+        with_task_manager(|task_manager| task_manager.tasks.push(future_task));
+        id
+    }
+
+    /// Add task to task manager with the specified scheduling flags. Returns identifier of the task.
+    /// With [TaskFlags::EXCLUSIVE_BURST] the task runs up to `burst_iterations` consecutive loop iterations
+    /// before task manager switches to the next task. Cooperative tasks are never preempted,
+    /// so [TaskFlags::NO_PREEMPT] has no effect here.
+    pub fn add_task_with_flags(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        flags: TaskFlags,
+        burst_iterations: u32,
+    ) -> TaskIdType {
+        let mut future_task =
+            Self::create_task(setup_fn, loop_fn, stop_condition_fn, RestartPolicy::Never);
+        future_task.flags = flags;
+        future_task.burst_iterations = burst_iterations;
+        let id = future_task.id;
+        with_task_manager(|task_manager| task_manager.tasks.push(future_task));
+        id
+    }
+
+    /// Add task with the specified name to task manager. Returns identifier of the task.
+    /// Names are used only for debugging and may be duplicated. The first duplicated name
+    /// is reported by the diagnostics dump.
+    pub fn add_named_task(
+        name: &'static str,
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+    ) -> TaskIdType {
+        Self::add_task_with_name(TaskName::Static(name), setup_fn, loop_fn, stop_condition_fn)
+    }
+
+    /// Add task with the specified name to task manager. Returns identifier of the task.
+    pub(crate) fn add_task_with_name(
+        name: TaskName,
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+    ) -> TaskIdType {
+        let mut future_task =
+            Self::create_task(setup_fn, loop_fn, stop_condition_fn, RestartPolicy::Never);
+        future_task.name = Some(name);
+        let id = future_task.id;
+        with_task_manager(|task_manager| {
+            if task_manager.duplicate_name.is_none()
+                && task_manager.tasks.iter().any(|task| {
+                    task.name
+                        .is_some_and(|task_name| task_name.as_str() == name.as_str())
+                })
+            {
+                task_manager.duplicate_name = Some(name);
+            }
+            task_manager.tasks.push(future_task)
+        });
+        id
+    }
+
+    /// Add task, that is child of the task with the specified identifier. When the parent is completed,
+    /// the child and all its descendants are completed too with [ExitReason::ParentFinished].
+    /// Returns identifier of the child or None if there is no parent task or it is already finished or terminated,
+    /// because such child would never be completed together with its parent.
+    pub fn spawn_child(
+        parent_id: TaskIdType,
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+    ) -> Option<TaskIdType> {
+        with_task_manager(|task_manager| {
+            task_manager
+                .find_task(parent_id)
+                .filter(|parent| !parent.is_finished)
+                .map(|_| ())
+        })?;
+        let mut future_task =
+            Self::create_task(setup_fn, loop_fn, stop_condition_fn, RestartPolicy::Never);
+        future_task.parent = Some(parent_id);
+        let id = future_task.id;
+        with_task_manager(|task_manager| task_manager.tasks.push(future_task));
+        Some(id)
+    }
+
+    /// Makes the task child of another task. Returns false if any of the tasks does not exist
+    /// or the new parent is the task itself or its descendant.
+    pub fn adopt(child_id: TaskIdType, parent_id: TaskIdType) -> bool {
+        with_task_manager(|task_manager| {
+            if task_manager.find_task(child_id).is_none() {
+                return false;
+            }
+            // Walk up from the new parent to make sure the child is not its ancestor.
+            let mut ancestor = Some(parent_id);
+            while let Some(id) = ancestor {
+                if id == child_id {
+                    return false;
+                }
+                match task_manager.find_task(id) {
+                    Some(future_task) => ancestor = future_task.parent,
+                    None => return false,
+                }
+            }
+            task_manager.find_task(child_id).unwrap().parent = Some(parent_id);
+            true
+        })
+    }
+
+    /// Detaches the task from its parent, so it is not completed together with the parent.
+    /// Returns false if there is no task with such identifier.
+    pub fn detach(child_id: TaskIdType) -> bool {
+        with_task_manager(|task_manager| match task_manager.find_task(child_id) {
+            Some(future_task) => {
+                future_task.parent = None;
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Puts the task with the specified identifier to sleep. Sleeping task is not executed
+    /// until it is woken up with [CooperativeTaskManager::try_wake_up_task]. Task may put itself to sleep.
+    pub fn try_put_to_sleep(id: TaskIdType) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| {
+            let future_task = task_manager.find_task(id).ok_or(TaskError::NotFound)?;
+            if future_task.is_finished || future_task.is_sleeping {
+                return Err(TaskError::InvalidState);
+            }
+            future_task.is_sleeping = true;
+            Ok(())
+        })
+    }
+
+    /// Wakes up the sleeping task with the specified identifier.
+    /// The task runs, when its turn comes in round-robin order.
+    pub fn try_wake_up_task(id: TaskIdType) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| task_manager.wake_up(id, false))
+    }
+
+    /// Wakes up the sleeping task with the specified identifier like [CooperativeTaskManager::try_wake_up_task],
+    /// but the task runs on the next step of task manager before the next task in round-robin order.
+    /// Tasks woken up this way run in order of waking up, then round-robin order continues
+    /// from the task, that was skipped for them.
+    pub fn try_wake_up_task_prioritized(id: TaskIdType) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| task_manager.wake_up(id, true))
+    }
+
+    /// Wakes up the task with the specified identifier because of an event, e.g. from an interrupt handler,
+    /// so it runs on the next step of task manager ahead of other tasks exactly once
+    /// like a task woken up with [CooperativeTaskManager::try_wake_up_task_prioritized].
+    /// Task, that does not sleep, is boosted too. Repeated events before the task runs
+    /// do not stack, the task still runs ahead of other tasks once.
+    pub fn try_wake_up_task_from_event(id: TaskIdType) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| {
+            let index = task_manager.position(id).ok_or(TaskError::NotFound)?;
+            if task_manager.tasks[index].is_finished {
+                return Err(TaskError::InvalidState);
+            }
+            if task_manager.tasks[index].is_sleeping {
+                task_manager.mark_woken(index);
+            }
+            let future_task = &mut task_manager.tasks[index];
+            if !future_task.is_boosted {
+                future_task.is_boosted = true;
+                future_task.boosted_wakes = future_task.boosted_wakes.saturating_add(1);
+                task_manager.front_queue.push_back(id);
+            }
+            Ok(())
+        })
+    }
+
+    /// Wakes up the sleeping task with the specified identifier and records time of waking up
+    /// to measure wake latency. Prioritized task is put to the front queue.
+    fn wake_up(&mut self, id: TaskIdType, prioritized: bool) -> Result<(), TaskError> {
+        let index = self.position(id).ok_or(TaskError::NotFound)?;
+        if !self.tasks[index].is_sleeping {
+            return Err(TaskError::InvalidState);
+        }
+        self.mark_woken(index);
+        if prioritized {
+            self.front_queue.push_back(id);
+        }
+        Ok(())
+    }
+
+    /// Marks the sleeping task with the specified index as woken up at the current step.
+    fn mark_woken(&mut self, index: usize) {
+        let future_task = &mut self.tasks[index];
+        future_task.is_sleeping = false;
+        future_task.wake_at = None;
+        future_task.waits_for = None;
+        future_task.woken_at = Some((self.step_count, clock_us()));
+    }
+
+    /// Puts the task with the specified identifier to sleep for the specified duration.
+    /// The task is woken up automatically, when its turn comes after the duration passes,
+    /// or earlier with [CooperativeTaskManager::try_wake_up_task].
+    /// Deadlines are measured by the system clock, a hardware timer reserved by [crate::init_system]
+    /// and not available to the user. Returns [TaskError::ClockUnavailable], if the port has no timer
+    /// for the clock or the system is not initialized.
+    pub fn try_sleep_for(id: TaskIdType, duration: Duration) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| {
+            let future_task = task_manager.find_task(id).ok_or(TaskError::NotFound)?;
+            if future_task.is_finished || future_task.is_sleeping {
+                return Err(TaskError::InvalidState);
+            }
+            let now_us = clock_us().ok_or(TaskError::ClockUnavailable)?;
+            let duration_us = u64::try_from(duration.as_micros())
+                .unwrap_or(u64::MAX)
+                .min(MAX_SLEEP_US);
+            let future_task = task_manager.find_task(id).unwrap();
+            future_task.is_sleeping = true;
+            future_task.wake_at = Some(now_us.wrapping_add(duration_us));
+            Ok(())
+        })
+    }
+
+    /// Wakes up the task with the specified index, if it sleeps for a duration and the duration has passed.
+    fn wake_up_if_due(&mut self, index: usize) {
+        let (Some(wake_at), Some(now_us)) = (self.tasks[index].wake_at, clock_us()) else {
+            return;
+        };
+        if time_until(wake_at, now_us).is_none() {
+            self.mark_woken(index);
+        }
+    }
+
+    /// Puts the task, that is being executed, to sleep until the task with the specified identifier
+    /// is completed by its stop condition, terminated, completed together with its parent or deleted.
+    /// Several tasks may wait for one task. The calling task falls asleep after its current iteration.
+    /// Returns [JoinHandleState::AlreadyTerminated] without sleeping, if the task is already completed
+    /// or there is no such task. Returns [TaskError::NotFound], if it is not called from a task,
+    /// and [TaskError::InvalidState], if the task waits for itself.
+    pub fn try_wait_for_termination(id: TaskIdType) -> Result<JoinHandleState, TaskError> {
+        with_task_manager(|task_manager| {
+            let current_id = task_manager.current_task_id.ok_or(TaskError::NotFound)?;
+            if current_id == id {
+                return Err(TaskError::InvalidState);
+            }
+            let Some(index) = task_manager.position(id) else {
+                return Ok(JoinHandleState::AlreadyTerminated);
+            };
+            if task_manager.tasks[index].is_finished {
+                return Ok(JoinHandleState::AlreadyTerminated);
+            }
+            task_manager.tasks[index].joiners.push(current_id);
+            let caller = task_manager.find_task(current_id).unwrap();
+            caller.is_sleeping = true;
+            caller.wake_at = None;
+            caller.waits_for = Some(id);
+            Ok(JoinHandleState::Waiting)
+        })
+    }
+
+    /// Waits for termination of the task like [CooperativeTaskManager::try_wait_for_termination],
+    /// but panics on error.
+    pub fn wait_for_termination(id: TaskIdType) -> JoinHandleState {
+        match Self::try_wait_for_termination(id) {
+            Ok(state) => state,
+            Err(error) => panic!("can not wait for termination of task {}: {}", id, error),
+        }
+    }
+
+    /// Enables detection of deadlocks. When no task can run and no task sleeps until a deadline,
+    /// task manager looks for a cycle of tasks waiting for termination of each other
+    /// with [CooperativeTaskManager::try_wait_for_termination] and calls the hook once per deadlock.
+    /// Tasks, that sleep until they are woken up by identifier, may be woken up by an interrupt handler,
+    /// so they are never reported. Martos has no output of its own, so without the hook deadlocks
+    /// are not reported. None disables detection.
+    pub fn on_deadlock(hook: Option<DeadlockHookType>) {
+        with_task_manager(|task_manager| {
+            task_manager.deadlock_hook = hook;
+            task_manager.is_deadlock_reported = false;
+        });
+    }
+
+    /// Returns reason, why the task sleeps, or None if it does not sleep.
+    fn wait_reason(future_task: &FutureTask) -> Option<WaitReason> {
+        if !future_task.is_sleeping || future_task.is_finished {
+            None
+        } else if let Some(id) = future_task.waits_for {
+            Some(WaitReason::Join(id))
+        } else if future_task.wake_at.is_some() {
+            Some(WaitReason::Deadline)
+        } else {
+            Some(WaitReason::WakeUp)
         }
     }
 
-    /// One step of task manager's work.
+    /// Returns the first cycle of tasks waiting for termination of each other or None if there is no cycle.
+    fn find_join_cycle(&self) -> Option<Vec<(TaskIdType, WaitReason)>> {
+        for future_task in self.tasks.iter() {
+            let mut chain: Vec<(TaskIdType, WaitReason)> = Vec::new();
+            let mut id = future_task.id;
+            loop {
+                if let Some(start) = chain.iter().position(|&(chain_id, _)| chain_id == id) {
+                    return Some(chain.split_off(start));
+                }
+                let Some(task) = self.tasks.iter().find(|task| task.id == id) else {
+                    break;
+                };
+                match Self::wait_reason(task) {
+                    Some(WaitReason::Join(target)) => {
+                        chain.push((id, WaitReason::Join(target)));
+                        id = target;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        None
+    }
+
+    /// Calls the deadlock hook, if no task can make progress and tasks wait for each other in a cycle.
+    fn check_deadlock() {
+        let deadlock = with_task_manager(|task_manager| {
+            let hook = task_manager.deadlock_hook?;
+            let is_stalled = task_manager.tasks.iter().all(|future_task| {
+                future_task.is_finished
+                    || (future_task.is_sleeping && future_task.wake_at.is_none())
+            });
+            if !is_stalled || task_manager.is_deadlock_reported {
+                return None;
+            }
+            let tasks = task_manager.find_join_cycle()?;
+            task_manager.is_deadlock_reported = true;
+            Some((hook, DeadlockReport { tasks }))
+        });
+        // Hook may use task manager, so it is called outside of critical section.
+        if let Some((hook, report)) = deadlock {
+            hook(&report);
+        }
+    }
+
+    /// Wakes up all sleeping tasks, that wait for completion of the task with the specified index.
+    fn wake_joiners(&mut self, index: usize) {
+        let joiners = core::mem::take(&mut self.tasks[index].joiners);
+        for id in joiners {
+            if let Some(joiner_index) = self.position(id) {
+                if self.tasks[joiner_index].is_sleeping {
+                    self.mark_woken(joiner_index);
+                }
+            }
+        }
+    }
+
+    /// Removes the task with the specified identifier from task manager without calling its exit hook.
+    /// Not completed descendants of the task are completed with [ExitReason::ParentFinished].
+    /// Task can not delete itself.
+    pub fn try_delete_task(id: TaskIdType) -> Result<(), TaskError> {
+        with_task_manager(|task_manager| {
+            let index = task_manager.position(id).ok_or(TaskError::NotFound)?;
+            if task_manager.current_task_id == Some(id) {
+                return Err(TaskError::CurrentlyRunning);
+            }
+            task_manager.wake_joiners(index);
+            task_manager.remove_task(index);
+            Ok(())
+        })?;
+        Self::finish_descendants(id);
+        Ok(())
+    }
+
+    /// Returns index of the task with the specified identifier.
+    fn position(&self, id: TaskIdType) -> Option<usize> {
+        self.tasks
+            .iter()
+            .position(|future_task| future_task.id == id)
+    }
+
+    /// Removes the task with the specified index and keeps index of the next task to execute valid.
+    fn remove_task(&mut self, index: usize) {
+        self.tasks.remove(index);
+        if index < self.task_to_execute_index {
+            self.task_to_execute_index -= 1;
+        } else if index == self.task_to_execute_index {
+            self.burst_count = 0;
+            if index >= self.tasks.len() {
+                self.task_to_execute_index = 0;
+            }
+        }
+    }
+
+    /// Puts the task to sleep like [CooperativeTaskManager::try_put_to_sleep], but panics on error.
+    pub fn put_to_sleep(id: TaskIdType) {
+        if let Err(error) = Self::try_put_to_sleep(id) {
+            panic!("can not put task {} to sleep: {}", id, error);
+        }
+    }
+
+    /// Wakes up the task like [CooperativeTaskManager::try_wake_up_task], but panics on error.
+    pub fn wake_up_task(id: TaskIdType) {
+        if let Err(error) = Self::try_wake_up_task(id) {
+            panic!("can not wake up task {}: {}", id, error);
+        }
+    }
+
+    /// Wakes up the task like [CooperativeTaskManager::try_wake_up_task_from_event], but panics on error.
+    pub fn wake_up_task_from_event(id: TaskIdType) {
+        if let Err(error) = Self::try_wake_up_task_from_event(id) {
+            panic!("can not wake up task {}: {}", id, error);
+        }
+    }
+
+    /// Wakes up the task like [CooperativeTaskManager::try_wake_up_task_prioritized], but panics on error.
+    pub fn wake_up_task_prioritized(id: TaskIdType) {
+        if let Err(error) = Self::try_wake_up_task_prioritized(id) {
+            panic!("can not wake up task {}: {}", id, error);
+        }
+    }
+
+    /// Puts the task to sleep like [CooperativeTaskManager::try_sleep_for], but panics on error.
+    pub fn sleep_for(id: TaskIdType, duration: Duration) {
+        if let Err(error) = Self::try_sleep_for(id, duration) {
+            panic!("can not put task {} to sleep: {}", id, error);
+        }
+    }
+
+    /// Deletes the task like [CooperativeTaskManager::try_delete_task], but panics on error.
+    pub fn delete_task(id: TaskIdType) {
+        if let Err(error) = Self::try_delete_task(id) {
+            panic!("can not delete task {}: {}", id, error);
+        }
+    }
+
+    /// Completes all not completed descendants of the task and calls their exit hooks.
+    /// Children are completed after their parent, depth-first.
+    fn finish_descendants(parent_id: TaskIdType) {
+        let mut parents = Vec::new();
+        parents.push(parent_id);
+        while let Some(&parent_id) = parents.last() {
+            let child = with_task_manager(|task_manager| {
+                let index = task_manager.tasks.iter().position(|future_task| {
+                    future_task.parent == Some(parent_id) && !future_task.is_finished
+                })?;
+                task_manager.tasks[index].is_finished = true;
+                task_manager.wake_joiners(index);
+                Some(task_manager.tasks[index].iteration())
+            });
+            match child {
+                Some(iteration) => {
+                    parents.push(iteration.id);
+                    // Exit hook may add tasks, so it is called outside of critical section.
+                    iteration.notify_exit(ExitReason::ParentFinished);
+                }
+                None => {
+                    parents.pop();
+                }
+            }
+        }
+    }
+
+    /// Add task with inbox of the specified capacity to task manager. Returns identifier of the task.
+    /// Messages can be sent to the task with [CooperativeTaskManager::send_to]
+    /// and received by the task with [CooperativeTaskManager::recv].
+    pub fn add_task_with_inbox(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        inbox_capacity: usize,
+    ) -> TaskIdType {
+        let mut future_task =
+            Self::create_task(setup_fn, loop_fn, stop_condition_fn, RestartPolicy::Never);
+        future_task.inbox = Some(Inbox {
+            messages: VecDeque::with_capacity(inbox_capacity),
+            capacity: inbox_capacity,
+            dropped: 0,
+        });
+        let id = future_task.id;
+        with_task_manager(|task_manager| task_manager.tasks.push(future_task));
+        id
+    }
+
+    /// Sends message to inbox of the task with the specified identifier.
+    /// If the inbox is full, the message is dropped and counted.
+    pub fn send_to(id: TaskIdType, message: InboxMessage) -> Result<(), InboxError> {
+        Self::send(id, message, false)
+    }
+
+    /// Sends message like [CooperativeTaskManager::send_to] and wakes up the task,
+    /// if it sleeps and the message is put to its inbox.
+    pub fn send_to_and_wake(id: TaskIdType, message: InboxMessage) -> Result<(), InboxError> {
+        Self::send(id, message, true)
+    }
+
+    /// Sends message to inbox of the task and optionally wakes up the task after delivery.
+    fn send(id: TaskIdType, message: InboxMessage, wake: bool) -> Result<(), InboxError> {
+        with_task_manager(|task_manager| {
+            let future_task = task_manager.find_task(id).ok_or(InboxError::NoTask)?;
+            if future_task.is_finished {
+                return Err(InboxError::TaskFinished);
+            }
+            let inbox = future_task.inbox.as_mut().ok_or(InboxError::NoInbox)?;
+            if inbox.messages.len() >= inbox.capacity {
+                inbox.dropped += 1;
+                return Err(InboxError::Full);
+            }
+            inbox.messages.push_back(message);
+            if wake {
+                future_task.is_sleeping = false;
+                future_task.wake_at = None;
+            }
+            Ok(())
+        })
+    }
+
+    /// Receives the oldest message from inbox of the task, that is being executed.
+    /// Returns None if there are no messages or it is called outside of task functions.
+    pub fn recv() -> Option<InboxMessage> {
+        with_task_manager(|task_manager| {
+            let id = task_manager.current_task_id?;
+            task_manager
+                .find_task(id)?
+                .inbox
+                .as_mut()?
+                .messages
+                .pop_front()
+        })
+    }
+
+    /// Calls the function for every task in order of adding without copying task state.
+    /// The function is called inside of critical section and must not use task manager.
+    pub fn for_each_task(mut f: impl FnMut(&TaskInfoRef)) {
+        with_task_manager(|task_manager| {
+            for future_task in task_manager.tasks.iter() {
+                f(&TaskInfoRef { future_task });
+            }
+        });
+    }
+
+    /// Returns state of the task with the specified identifier or None if there is no such task.
+    /// Cooperative task manager has no priorities, so priority of the task is always 0.
+    pub fn get_task_info(id: TaskIdType) -> Option<TaskInfo> {
+        with_task_manager(|task_manager| {
+            task_manager
+                .tasks
+                .iter()
+                .find(|future_task| future_task.id == id)
+                .map(|future_task| task_manager.task_info(future_task))
+        })
+    }
+
+    /// Returns states of all tasks in order of adding. The result is a copy,
+    /// so it can be iterated by a task, that adds or deletes tasks. Priorities of the tasks are always 0.
+    pub fn list_tasks() -> Vec<TaskInfo> {
+        with_task_manager(|task_manager| {
+            task_manager
+                .tasks
+                .iter()
+                .map(|future_task| task_manager.task_info(future_task))
+                .collect()
+        })
+    }
+
+    /// Returns state of the task.
+    fn task_info(&self, future_task: &FutureTask) -> TaskInfo {
+        let status = if future_task.is_finished {
+            TaskStatus::Finished
+        } else if self.current_task_id == Some(future_task.id) {
+            TaskStatus::Running
+        } else if future_task.is_sleeping {
+            TaskStatus::Sleeping
+        } else {
+            TaskStatus::Ready
+        };
+        TaskInfo {
+            id: future_task.id,
+            priority: 0,
+            status,
+            restart_count: future_task.restart_count,
+            flags: future_task.flags,
+            name: future_task
+                .name
+                .map_or([0; TASK_NAME_MAX_LEN], TaskName::to_buffer),
+            inbox_dropped: future_task.inbox.as_ref().map_or(0, |inbox| inbox.dropped),
+            wake_latency_iterations: future_task.wake_latency_iterations,
+            boosted_wakes: future_task.boosted_wakes,
+            wake_latency_us: future_task.wake_latency_us,
+        }
+    }
+
+    /// Returns task with the specified identifier.
+    fn find_task(&mut self, id: TaskIdType) -> Option<&mut FutureTask> {
+        self.tasks
+            .iter_mut()
+            .find(|future_task| future_task.id == id)
+    }
+
+    /// Returns identifier of the first task with the specified name.
+    pub fn find_task_by_name(name: &str) -> Option<TaskIdType> {
+        with_task_manager(|task_manager| {
+            task_manager
+                .tasks
+                .iter()
+                .find(|future_task| {
+                    future_task
+                        .name
+                        .is_some_and(|task_name| task_name.as_str() == name)
+                })
+                .map(|future_task| future_task.id)
+        })
+    }
+
+    /// Sets hooks, that are called before and after every iteration of task manager, e.g. for profiling.
+    /// Post-iteration hook gets indicator whether loop function of the task was called.
+    /// Hooks are executed in task manager context with the same restrictions as loop functions.
+    pub fn set_iteration_hooks(
+        pre_iteration_hook: Option<TaskPreIterationHookType>,
+        post_iteration_hook: Option<TaskPostIterationHookType>,
+    ) {
+        with_task_manager(|task_manager| {
+            task_manager.pre_iteration_hook = pre_iteration_hook;
+            task_manager.post_iteration_hook = post_iteration_hook;
+        });
+    }
+
+    /// Sets exit hook of the task with the specified identifier.
+    /// The hook is called every time stop condition of the task is reached: with [ExitReason::Restarted]
+    /// before setup function of the restarted task and with [ExitReason::StopCondition] once, when the task completes.
+    /// Returns false if there is no task with such identifier.
+    pub fn set_exit_hook(id: TaskIdType, exit_hook: TaskExitHookType) -> bool {
+        with_task_manager(|task_manager| match task_manager.find_task(id) {
+            Some(future_task) => {
+                future_task.exit_hook = Some(exit_hook);
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Sets whether setup function of the task with the specified identifier is called again,
+    /// when the task is restarted by its [RestartPolicy]. If it is not, the restarted task
+    /// continues with its loop function and keeps the state prepared by the first setup.
+    /// Returns false if there is no task with such identifier.
+    pub fn set_restart_setup(id: TaskIdType, rerun_setup: bool) -> bool {
+        with_task_manager(|task_manager| match task_manager.find_task(id) {
+            Some(future_task) => {
+                future_task.rerun_setup = rerun_setup;
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Add batch of tasks to task manager. Returns identifiers of the tasks in batch order.
+    /// At first all tasks get their identifiers, then setup functions are called in batch order,
+    /// and only after that the tasks are added to task manager.
+    pub fn add_tasks(batch: &[TaskSpec]) -> Vec<TaskIdType> {
+        let mut future_tasks: Vec<FutureTask> = batch
+            .iter()
+            .map(|spec| {
+                Self::create_task(
+                    spec.setup_fn,
+                    spec.loop_fn,
+                    spec.stop_condition_fn,
+                    RestartPolicy::Never,
+                )
+            })
+            .collect();
+        let ids = future_tasks
+            .iter()
+            .map(|future_task| future_task.id)
+            .collect();
+
+        for future_task in future_tasks.iter_mut() {
+            (future_task.task.setup_fn)();
+            future_task.is_setup_completed = true;
+        }
+
+        with_task_manager(|task_manager| task_manager.tasks.append(&mut future_tasks));
+        ids
+    }
+
+    /// Creates new task with the next task identifier.
+    fn create_task(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        restart_policy: RestartPolicy,
+    ) -> FutureTask {
+        let id = with_task_manager(|task_manager| {
+            let id = task_manager.next_task_id;
+            task_manager.next_task_id += 1;
+            id
+        });
+
+        FutureTask {
+            id,
+            task: Task {
+                setup_fn,
+                loop_fn,
+                stop_condition_fn,
+            },
+            is_setup_completed: false,
+            restart_policy,
+            restart_count: 0,
+            rerun_setup: true,
+            exit_hook: None,
+            is_finished: false,
+            flags: TaskFlags::NONE,
+            burst_iterations: 0,
+            name: None,
+            inbox: None,
+            parent: None,
+            is_sleeping: false,
+            wake_at: None,
+            is_terminated: false,
+            woken_at: None,
+            wake_latency_iterations: 0,
+            wake_latency_us: 0,
+            joiners: Vec::new(),
+            waits_for: None,
+            is_boosted: false,
+            boosted_wakes: 0,
+            cancel_token: CancelToken::new(),
+        }
+    }
+
+    /// Runs one step of task manager's work and returns control to the caller.
+    /// It allows to embed task manager into another environment, for example, into a task of another RTOS,
+    /// that calls this function in its own loop and may sleep until returned deadline.
+    /// Repeated calls behave the same way as [TaskManagerTrait::start_task_manager].
+    /// Preemptive task manager switches threads only in the timer interrupt, so it has no such function.
+    pub fn run_one_iteration() -> IterationOutcome {
+        let task_ran = Self::task_manager_step();
+        let next_deadline_us = with_task_manager(|task_manager| {
+            let has_pending_tasks = task_manager
+                .tasks
+                .iter()
+                .any(|future_task| !future_task.is_finished && !future_task.is_sleeping);
+            if has_pending_tasks {
+                return Some(0);
+            }
+            // Sleeping task with passed deadline is woken up on the next iteration.
+            clock_us().and_then(|now_us| {
+                task_manager
+                    .tasks
+                    .iter()
+                    .filter_map(|future_task| future_task.wake_at)
+                    .map(|wake_at| time_until(wake_at, now_us).unwrap_or(0))
+                    .min()
+            })
+        });
+        IterationOutcome {
+            task_ran,
+            next_deadline_us,
+        }
+    }
+
+    /// One step of task manager's work. Returns whether function of some task was called.
     // TODO: Support priorities.
     // TODO: Delete tasks from task vector if they are pending?
-    fn task_manager_step() {
-        if unsafe { !TASK_MANAGER.tasks.is_empty() } {
-            let waker = task_waker();
-
-            let task = unsafe { &mut TASK_MANAGER.tasks[TASK_MANAGER.task_to_execute_index] };
-            let mut task_future_pin = Pin::new(task);
-            let _ = task_future_pin
-                .as_mut()
-                .poll(&mut Context::from_waker(&waker));
-
-            unsafe {
-                if TASK_MANAGER.task_to_execute_index + 1 < TASK_MANAGER.tasks.len() {
-                    TASK_MANAGER.task_to_execute_index += 1;
-                } else {
-                    TASK_MANAGER.task_to_execute_index = 0;
+    fn task_manager_step() -> bool {
+        let step = with_task_manager(|task_manager| {
+            if task_manager.tasks.is_empty() {
+                return None;
+            }
+            task_manager.step_count = task_manager.step_count.wrapping_add(1);
+            if !task_manager.front_queue.is_empty() && task_manager.burst_count == 0 {
+                // Tasks may be woken up between steps, e.g. by interrupt handlers.
+                let next_index = task_manager.task_to_execute_index;
+                task_manager.select_next(next_index);
+            }
+            let index = task_manager.task_to_execute_index;
+            task_manager.wake_up_if_due(index);
+            task_manager.record_wake_latency(index);
+            let task = &task_manager.tasks[task_manager.task_to_execute_index];
+            Some((
+                task.iteration(),
+                // Setup call and skipped iteration of sleeping task do not count as an iteration of burst.
+                task.is_setup_completed && !task.is_sleeping,
+                task.burst_length(),
+                task_manager.pre_iteration_hook,
+                task_manager.post_iteration_hook,
+            ))
+        });
+        let Some((
+            iteration,
+            is_loop_iteration,
+            burst_length,
+            pre_iteration_hook,
+            post_iteration_hook,
+        )) = step
+        else {
+            return false;
+        };
+
+        // Task functions and hooks may add or delete tasks, so they are called outside of critical section.
+        if let Some(pre_iteration_hook) = pre_iteration_hook {
+            pre_iteration_hook(iteration.id);
+        }
+        with_task_manager(|task_manager| task_manager.current_task_id = Some(iteration.id));
+        let transition = iteration.run();
+
+        let poll_result = with_task_manager(|task_manager| {
+            task_manager.current_task_id = None;
+            match task_manager.position(iteration.id) {
+                Some(index) => {
+                    let poll_result = task_manager.tasks[index].apply(transition);
+                    if poll_result.is_ready() {
+                        task_manager.wake_joiners(index);
+                    }
+                    poll_result
+                }
+                None => Poll::Ready(()),
+            }
+        });
+        if transition == TaskTransition::Finished && !iteration.is_finished {
+            Self::finish_descendants(iteration.id);
+        }
+        if let Some(post_iteration_hook) = post_iteration_hook {
+            post_iteration_hook(iteration.id, transition == TaskTransition::LoopCompleted);
+        }
+
+        with_task_manager(|task_manager| {
+            if !iteration.is_finished && !iteration.is_sleeping {
+                // Task ran, so the next deadlock is a new one.
+                task_manager.is_deadlock_reported = false;
+            }
+            if let Some(index) = task_manager.position(iteration.id) {
+                if task_manager.tasks[index].is_terminated {
+                    // The next task takes index of the removed one, so the index is not advanced.
+                    task_manager.remove_task(index);
+                    let next_index = task_manager.task_to_execute_index;
+                    task_manager.select_next(next_index);
+                    return;
                 }
             }
+            if is_loop_iteration {
+                task_manager.burst_count += 1;
+            }
+            if poll_result.is_pending()
+                && transition != TaskTransition::Skipped
+                && task_manager.burst_count < burst_length
+            {
+                return;
+            }
+            task_manager.burst_count = 0;
+            let next_index = if task_manager.task_to_execute_index + 1 < task_manager.tasks.len() {
+                task_manager.task_to_execute_index + 1
+            } else {
+                0
+            };
+            task_manager.select_next(next_index);
+        });
+        let task_ran = !iteration.is_finished && !iteration.is_sleeping;
+        if !task_ran {
+            Self::check_deadlock();
+        }
+        task_ran
+    }
+
+    /// Records wake latency of the task with the specified index, if it runs for the first time after waking up.
+    fn record_wake_latency(&mut self, index: usize) {
+        let step_count = self.step_count;
+        let future_task = &mut self.tasks[index];
+        if future_task.is_sleeping || future_task.is_finished {
+            return;
+        }
+        if let Some((woken_step, woken_us)) = future_task.woken_at.take() {
+            future_task.wake_latency_iterations =
+                u32::try_from(step_count.wrapping_sub(woken_step)).unwrap_or(u32::MAX);
+            future_task.wake_latency_us = match (woken_us, clock_us()) {
+                (Some(woken_us), Some(now_us)) => now_us.wrapping_sub(woken_us),
+                _ => 0,
+            };
+        }
+    }
+
+    /// Selects the task for the next step: the first runnable task of the front queue
+    /// or the task with the specified index, that is the next one in round-robin order.
+    fn select_next(&mut self, next_index: usize) {
+        while let Some(id) = self.front_queue.pop_front() {
+            let Some(index) = self.position(id) else {
+                continue;
+            };
+            let future_task = &self.tasks[index];
+            if future_task.is_finished || future_task.is_sleeping {
+                continue;
+            }
+            if self.resume_task_id.is_none() && index != next_index {
+                self.resume_task_id = self.tasks.get(next_index).map(|task| task.id);
+            }
+            self.tasks[index].is_boosted = false;
+            self.task_to_execute_index = index;
+            return;
+        }
+        self.task_to_execute_index = match self.resume_task_id.take() {
+            Some(id) => self.position(id).unwrap_or(next_index),
+            None => next_index,
+        };
+    }
+
+    /// Writes states of all tasks for diagnostics, one task per line.
+    pub(crate) fn write_tasks(writer: &mut dyn Write) -> fmt::Result {
+        let (count, current, duplicate_name) = with_task_manager(|task_manager| {
+            (
+                task_manager.tasks.len(),
+                task_manager.task_to_execute_index,
+                task_manager.duplicate_name,
+            )
+        });
+        writeln!(writer, "count={}", count)?;
+        writeln!(writer, "current={}", current)?;
+        if let Some(name) = duplicate_name {
+            writeln!(writer, "warning=duplicate_name name={}", name.as_str())?;
+        }
+        let mut result = Ok(());
+        Self::for_each_task(|task| {
+            if result.is_ok() {
+                result = Self::write_task(writer, task);
+            }
+        });
+        result
+    }
+
+    /// Writes state of the task for diagnostics as one line.
+    fn write_task(writer: &mut dyn Write, task: &TaskInfoRef) -> fmt::Result {
+        write!(writer, "task id={} name=", task.id())?;
+        match task.name() {
+            Some(name) => write!(writer, "{}", name)?,
+            None => write!(writer, "task-{}", task.id())?,
+        }
+        write!(
+            writer,
+            " setup_completed={} restarts={}",
+            task.is_setup_completed(),
+            task.restart_count()
+        )?;
+        if let Some(parent) = task.parent() {
+            write!(writer, " parent={}", parent)?;
+        }
+        if let Some((len, capacity, dropped)) = task.inbox_stats() {
+            write!(
+                writer,
+                " inbox={}/{} inbox_dropped={}",
+                len, capacity, dropped
+            )?;
         }
+        writeln!(writer)
     }
 
     /// Starts task manager work. Returns after 1000 steps only for testing task_manager_step.
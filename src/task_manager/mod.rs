@@ -1,5 +1,10 @@
 extern crate alloc;
 
+use alloc::sync::Arc;
+use core::fmt;
+use core::ops::BitOr;
+use core::sync::atomic::{AtomicBool, Ordering};
+
 use crate::task_manager::task::{
     TaskLoopFunctionType, TaskSetupFunctionType, TaskStopConditionFunctionType,
 };
@@ -9,16 +14,241 @@ mod task;
 cfg_if::cfg_if! {
     if #[cfg(feature = "preemptive")] {
         pub(crate) mod preemptive;
+        pub use preemptive::{PeriodicSpec, AGING_SLICES, MAX_TASK_PRIORITY};
         pub type TaskManager = preemptive::PreemptiveTaskManager;
     } else {
-        mod cooperative;
+        pub(crate) mod cooperative;
+        pub use cooperative::{
+            DeadlockHookType, DeadlockReport, ExitReason, JoinHandleState, RestartPolicy,
+            TaskExitHookType, TaskInfoRef, TaskPostIterationHookType, TaskPreIterationHookType,
+            TaskSpec, WaitReason,
+        };
         pub type TaskManager = cooperative::CooperativeTaskManager;
     }
 }
 
-/// Operating system task manager.
-/// By default [cooperative::CooperativeTaskManager] is used
-static mut TASK_MANAGER: TaskManager = TaskManager::new();
+/// Type of task identifier.
+pub type TaskIdType = usize;
+
+/// Type of task priority. Tasks with greater value have higher priority.
+pub type TaskPriorityType = u8;
+
+/// Status of task in [TaskInfo].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    /// Task waits for its turn.
+    Ready = 0,
+    /// Functions of the task are being executed.
+    Running = 1,
+    /// Task sleeps until it is woken up.
+    Sleeping = 2,
+    /// Task is completed and is not executed anymore.
+    Finished = 3,
+}
+
+/// Scheduling flags of a task.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskFlags(u32);
+
+impl TaskFlags {
+    /// No flags.
+    pub const NONE: TaskFlags = TaskFlags(0);
+    /// Task runs several consecutive loop iterations before task manager switches to the next task.
+    pub const EXCLUSIVE_BURST: TaskFlags = TaskFlags(1);
+    /// Timer interrupt does not switch away from the task for a bounded number of time slices.
+    /// Cooperative task manager never preempts tasks, so there the flag has no effect.
+    pub const NO_PREEMPT: TaskFlags = TaskFlags(2);
+
+    /// Checks if all the specified flags are set.
+    pub fn contains(&self, flags: TaskFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+impl BitOr for TaskFlags {
+    type Output = TaskFlags;
+
+    fn bitor(self, rhs: TaskFlags) -> TaskFlags {
+        TaskFlags(self.0 | rhs.0)
+    }
+}
+
+/// Maximum length in bytes of task name passed through C API and stored in [TaskInfo].
+pub const TASK_NAME_MAX_LEN: usize = 16;
+
+/// Snapshot of task state for introspection.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskInfo {
+    /// Identifier of the task.
+    pub id: TaskIdType,
+    /// Priority of the task. Cooperative task manager has no priorities, so it is always 0 there.
+    pub priority: TaskPriorityType,
+    /// Status of the task.
+    pub status: TaskStatus,
+    /// Number of restarts of the task. Preemptive task manager does not restart tasks, so it is always 0 there.
+    pub restart_count: u32,
+    /// Scheduling flags of the task.
+    pub flags: TaskFlags,
+    /// Name of the task padded with zeros and truncated to [TASK_NAME_MAX_LEN] bytes.
+    /// Unnamed task has only zeros there. Preemptive task manager has no named tasks,
+    /// so there it is always zeros.
+    pub name: [u8; TASK_NAME_MAX_LEN],
+    /// Number of messages dropped because inbox of the task was full.
+    pub inbox_dropped: u32,
+    /// Number of task manager steps between the last wake up of the task and its next run.
+    /// Preemptive task manager has no sleeping tasks, so it is always 0 there.
+    pub wake_latency_iterations: u32,
+    /// Number of events, that made the task run ahead of other tasks.
+    /// Preemptive task manager has no such events, so it is always 0 there.
+    pub boosted_wakes: u32,
+    /// Time in microseconds between the last wake up of the task and its next run by the system clock.
+    /// It is 0 without the clock and in preemptive task manager.
+    pub wake_latency_us: u64,
+}
+
+impl TaskInfo {
+    /// Returns name of the task or None if the task is unnamed. Tasks of preemptive task manager are always unnamed.
+    pub fn name(&self) -> Option<&str> {
+        let len = self
+            .name
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(TASK_NAME_MAX_LEN);
+        if len == 0 {
+            return None;
+        }
+        core::str::from_utf8(&self.name[..len]).ok()
+    }
+}
+
+/// Message for task inbox. It is small and has fixed size to be passed through C API.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InboxMessage {
+    /// Application-defined message kind.
+    pub tag: u32,
+    /// Message payload.
+    pub payload: [u8; 8],
+}
+
+/// Result of one iteration of task manager, that is run by external environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IterationOutcome {
+    /// Indicator whether function of some task was called.
+    pub task_ran: bool,
+    /// Time in microseconds until task manager has work to do. Some(0) means that work is pending,
+    /// None means that all tasks are completed or sleep until they are woken up.
+    pub next_deadline_us: Option<u64>,
+}
+
+/// Errors of sending message to task inbox.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InboxError {
+    /// Task with the specified identifier does not exist.
+    NoTask,
+    /// Task does not have inbox.
+    NoInbox,
+    /// Inbox of the task is full. The message is dropped.
+    Full,
+    /// Task is completed and does not receive messages.
+    TaskFinished,
+}
+
+impl InboxError {
+    /// Returns stable error code of the error. Inbox error codes are in range `-120..=-139`.
+    pub fn error_code(&self) -> i32 {
+        match self {
+            InboxError::NoTask => -120,
+            InboxError::NoInbox => -121,
+            InboxError::Full => -122,
+            InboxError::TaskFinished => -123,
+        }
+    }
+}
+
+impl fmt::Display for InboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InboxError::NoTask => write!(f, "task with this identifier does not exist"),
+            InboxError::NoInbox => write!(f, "task does not have inbox"),
+            InboxError::Full => write!(f, "task inbox is full"),
+            InboxError::TaskFinished => write!(f, "task is completed"),
+        }
+    }
+}
+
+impl core::error::Error for InboxError {}
+
+/// Token, that tells a task to stop its work early. Every task has its own token, that is set,
+/// when the task is terminated, so long loop bodies and waiting helpers like
+/// [crate::timer::delay_ms_cancellable] can return [TaskError::Cancelled] before the loop function ends.
+/// Copies of the token stay valid after the task is removed.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates token, that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks if the token is cancelled. It is a relaxed atomic load, so it can be checked every iteration.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Cancels the token.
+    pub(crate) fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Errors of changing task state.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskError {
+    /// Task with the specified identifier does not exist.
+    NotFound,
+    /// Task is in a state, that does not allow the operation,
+    /// e.g. it is already sleeping, is not sleeping or is completed.
+    InvalidState,
+    /// Operation is not allowed for the task, whose functions are being executed.
+    CurrentlyRunning,
+    /// System clock is not available: the port has no timer for it or the system is not initialized.
+    ClockUnavailable,
+    /// Waiting is interrupted, because the task is terminated. See [CancelToken].
+    Cancelled,
+}
+
+impl TaskError {
+    /// Returns stable error code of the error. Task error codes are in range `-140..=-159`.
+    pub fn error_code(&self) -> i32 {
+        match self {
+            TaskError::NotFound => -140,
+            TaskError::InvalidState => -141,
+            TaskError::CurrentlyRunning => -142,
+            TaskError::ClockUnavailable => -143,
+            TaskError::Cancelled => -144,
+        }
+    }
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::NotFound => write!(f, "task with this identifier does not exist"),
+            TaskError::InvalidState => write!(f, "task state does not allow the operation"),
+            TaskError::CurrentlyRunning => write!(f, "task is being executed"),
+            TaskError::ClockUnavailable => write!(f, "system clock is not available"),
+            TaskError::Cancelled => write!(f, "task is terminated"),
+        }
+    }
+}
+
+impl core::error::Error for TaskError {}
 
 pub trait TaskManagerTrait {
     /// Add task to task manager. You should pass setup, loop and condition functions.
@@ -26,7 +256,34 @@ pub trait TaskManagerTrait {
         setup_fn: TaskSetupFunctionType,
         loop_fn: TaskLoopFunctionType,
         stop_condition_fn: TaskStopConditionFunctionType,
-    );
+    ) {
+        Self::add_task_with_id(setup_fn, loop_fn, stop_condition_fn);
+    }
+
+    /// Add task to task manager like [TaskManagerTrait::add_task]. Returns identifier of the task.
+    fn add_task_with_id(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+    ) -> TaskIdType;
+
+    /// Terminates the task with the specified identifier, so it is not executed anymore.
+    /// A task may terminate itself, then it is removed after its current function returns.
+    /// [CancelToken] of the task is cancelled before the task is marked as terminated,
+    /// so a task terminated by an interrupt handler can stop its current loop body early.
+    fn terminate_task(id: TaskIdType) -> Result<(), TaskError>;
+
+    /// Terminates the task, whose functions are being executed, like [TaskManagerTrait::terminate_task].
+    /// Returns [TaskError::NotFound] if it is called outside of task functions.
+    fn terminate_current_task() -> Result<(), TaskError>;
+
+    /// Returns identifier of the task, whose functions are being executed,
+    /// or None if it is called outside of task functions.
+    fn current_task_id() -> Option<TaskIdType>;
+
+    /// Returns [CancelToken] of the task, whose functions are being executed,
+    /// or None if it is called outside of task functions.
+    fn current_cancel_token() -> Option<CancelToken>;
 
     /// Starts task manager work.
     fn start_task_manager() -> !;
@@ -1,39 +1,201 @@
-use crate::ports::{Port, PortTrait, TrapFrame, STACK_ALIGN};
+use crate::ports::{Port, PreemptPort, TimerPort, TrapFrame, STACK_ALIGN};
 use crate::task_manager::task::{
     Task, TaskLoopFunctionType, TaskSetupFunctionType, TaskStopConditionFunctionType,
 };
-use crate::task_manager::{TaskManagerTrait, TASK_MANAGER};
+use crate::task_manager::{
+    CancelToken, TaskError, TaskFlags, TaskIdType, TaskInfo, TaskManagerTrait, TaskPriorityType,
+    TaskStatus, TASK_NAME_MAX_LEN,
+};
 use alloc::vec::Vec;
 use core::alloc::Layout;
+use core::cell::Cell;
+use core::fmt::{self, Write};
+use core::ops::RangeInclusive;
+use core::ptr::{addr_of, addr_of_mut};
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
+
+// Declare preemptive_tests file as child file to test private functions.
+#[cfg(test)]
+#[path = "../../tests/preemptive/preemptive_tests.rs"]
+mod preemptive_tests;
 
+/// Default size of thread stack in bytes.
 pub(crate) const THREAD_STACK_SIZE: usize = 1024; // TODO:
 
+/// Maximum priority of thread. Priorities are in range `0..=MAX_TASK_PRIORITY`.
+pub const MAX_TASK_PRIORITY: TaskPriorityType = 10;
+
+/// Number of time slices, that a ready thread waits before its priority is raised by one level.
+/// Raised priority is used only for selecting the next thread and is reset, when the thread is selected.
+pub const AGING_SLICES: u32 = 8;
+
+/// State of preemptive task manager.
+static mut TASK_MANAGER: PreemptiveTaskManager = PreemptiveTaskManager::new();
+
+/// Number of timer interrupts handled by the scheduler.
+static ISR_TICK_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Time of task manager clock in microseconds at the last context switch.
+/// 64-bit atomics are not available on every platform, so critical section is used.
+static LAST_SWITCH_UPTIME_US: critical_section::Mutex<Cell<Option<u64>>> =
+    critical_section::Mutex::new(Cell::new(None));
+
+/// Number of stalls of the timer interrupt reported by the supervisor.
+static STALL_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Supervisor of the timer interrupt, that detects stalls of preemption.
+struct StallSupervisor {
+    /// Number of supervisor checks between comparisons of the interrupt counter.
+    check_period: u32,
+    /// Function, that is called once per stall of the timer interrupt.
+    on_stall: fn(),
+    /// Number of checks since the last comparison.
+    checks: u32,
+    /// Value of the interrupt counter at the last comparison.
+    last_tick_count: u32,
+    /// Indicator whether the current stall has already been reported.
+    is_stalled: bool,
+}
+
+/// Specification of a periodic task for [PreemptiveTaskManager::add_rate_monotonic_set].
+#[derive(Clone, Copy)]
+pub struct PeriodicSpec {
+    /// Release period of the task. Loop function is called once per period.
+    pub period: Duration,
+    /// Setup function, that is called once at the beginning of task.
+    pub setup_fn: TaskSetupFunctionType,
+    /// Loop function, that is called once per period.
+    pub loop_fn: TaskLoopFunctionType,
+    /// Condition function for stopping loop function execution.
+    pub stop_condition_fn: TaskStopConditionFunctionType,
+}
+
+/// Returns name of the task status for diagnostics.
+fn status_name(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Ready => "ready",
+        TaskStatus::Running => "running",
+        TaskStatus::Sleeping => "sleeping",
+        TaskStatus::Finished => "finished",
+    }
+}
+
 pub(crate) struct Thread {
+    /// Identifier of the thread task
+    pub(crate) id: TaskIdType,
     /// Pointer to the memory allocated for stack
     pub(crate) stack: *mut u8,
+    /// Layout of the memory allocated for stack, that is used to deallocate it
+    pub(crate) stack_layout: Layout,
     /// **Arch specific** state of the registers at the moment of context switch
     pub(crate) context: TrapFrame,
     /// Task that is executed by this thread
     pub(crate) task: Task,
+    /// Priority of the thread
+    pub(crate) priority: TaskPriorityType,
+    /// Scheduling flags of the thread
+    pub(crate) flags: TaskFlags,
+    /// Maximum number of consecutive time slices, that the thread keeps because of its flags
+    pub(crate) max_slices: u32,
+    /// Number of consecutive thread selections, that passed the thread over
+    pub(crate) waited_slices: u32,
+    /// Release period of periodic thread in microseconds
+    pub(crate) period_us: Option<u64>,
+    /// Release time of the current or the next job of periodic thread or end of sleeping.
+    /// The thread is not selected before this time. None until the first job is started
+    pub(crate) release_us: Option<u64>,
+    /// Indicator whether jobs finished after their deadlines are counted
+    pub(crate) monitors_deadlines: bool,
+    /// Number of jobs finished after their deadlines
+    pub(crate) deadline_misses: u32,
+    /// Marker for thread, whose stop condition is reached or that is terminated.
+    /// Finished thread is not scheduled anymore and is removed at context switch
+    pub(crate) is_finished: bool,
+    /// Token, that is cancelled when the thread is terminated
+    pub(crate) cancel_token: CancelToken,
 }
 
 impl Thread {
     fn new(
+        id: TaskIdType,
         stack: *mut u8,
+        stack_layout: Layout,
         start: TaskSetupFunctionType,
         loop_: TaskLoopFunctionType,
         stop: TaskStopConditionFunctionType,
+        priority: TaskPriorityType,
     ) -> Self {
         Thread {
+            id,
             stack,
+            stack_layout,
             context: TrapFrame::default(),
             task: Task {
                 setup_fn: start,
                 loop_fn: loop_,
                 stop_condition_fn: stop,
             },
+            priority,
+            flags: TaskFlags::NONE,
+            max_slices: 0,
+            waited_slices: 0,
+            period_us: None,
+            release_us: None,
+            monitors_deadlines: false,
+            deadline_misses: 0,
+            is_finished: false,
+            cancel_token: CancelToken::new(),
         }
     }
+
+    /// Returns priority, that is used to select the next thread: priority of the thread
+    /// raised by one level per [AGING_SLICES] time slices of waiting.
+    fn effective_priority(&self) -> u32 {
+        self.priority as u32 + self.waited_slices / AGING_SLICES
+    }
+
+    /// Checks whether the thread can be selected: it is not finished and it is released.
+    /// Without the clock thread is always released.
+    fn is_ready(&self, now_us: Option<u64>) -> bool {
+        if self.is_finished {
+            return false;
+        }
+        match (self.release_us, now_us) {
+            (Some(release_us), Some(now_us)) => now_us >= release_us,
+            _ => true,
+        }
+    }
+
+    /// Starts the next job of the thread, that is being executed, if it is released.
+    /// The first job of periodic thread is released, when it is started.
+    fn try_start_current_job() -> bool {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let now_us = task_manager.clock_us();
+        let thread = &mut task_manager.tasks[task_manager.task_to_execute_index];
+        if thread.period_us.is_some() && thread.release_us.is_none() {
+            thread.release_us = now_us;
+        }
+        thread.is_ready(now_us)
+    }
+
+    /// Finishes the job of the thread, that is being executed: counts missed deadline
+    /// and moves release time of periodic thread to its next period.
+    fn finish_current_job() {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let now_us = task_manager.clock_us();
+        let thread = &mut task_manager.tasks[task_manager.task_to_execute_index];
+        if let (Some(period_us), Some(release_us), Some(now_us)) =
+            (thread.period_us, thread.release_us, now_us)
+        {
+            let next_release_us = release_us + period_us;
+            if thread.monitors_deadlines && now_us > next_release_us {
+                thread.deadline_misses += 1;
+            }
+            thread.release_us = Some(next_release_us);
+        }
+    }
+
     pub(crate) fn run_task(
         start: TaskSetupFunctionType,
         loop_: TaskLoopFunctionType,
@@ -41,11 +203,17 @@ impl Thread {
     ) {
         start();
         loop {
-            if stop() {
+            // Finished thread spins until a context switch removes it and frees its stack.
+            if PreemptiveTaskManager::is_current_thread_finished() || stop() {
+                PreemptiveTaskManager::finish_current_thread();
                 // TODO: yield
                 loop {}
             } else {
+                // Periodic thread spins until its next release, the scheduler does not select it before.
+                while !Self::try_start_current_job() {}
                 loop_();
+                Self::finish_current_job();
+                PreemptiveTaskManager::check_preemption();
             }
         }
     }
@@ -54,7 +222,15 @@ impl Thread {
 pub struct PreemptiveTaskManager {
     pub(crate) tasks: Vec<Thread>,
     pub(crate) task_to_execute_index: usize,
+    /// Identifier, that is assigned to the next added task.
+    next_task_id: TaskIdType,
     first_task: bool,
+    /// Supervisor of the timer interrupt, if enabled.
+    stall_supervisor: Option<StallSupervisor>,
+    /// Index of the system clock timer, if the port has one.
+    clock_timer: Option<u8>,
+    /// Number of consecutive timer interrupts, that kept the current thread because of its flags.
+    kept_slices: u32,
 }
 
 impl PreemptiveTaskManager {
@@ -62,18 +238,466 @@ impl PreemptiveTaskManager {
         PreemptiveTaskManager {
             tasks: Vec::new(),
             task_to_execute_index: 0,
+            next_task_id: 1,
             first_task: true,
+            stall_supervisor: None,
+            clock_timer: None,
+            kept_slices: 0,
+        }
+    }
+
+    /// Removes all threads and frees their stacks, so task manager starts from a clean state.
+    /// Should not be called after task manager is started.
+    pub(crate) fn reset() {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        for thread in task_manager.tasks.drain(..) {
+            unsafe { alloc::alloc::dealloc(thread.stack, thread.stack_layout) };
+        }
+        *task_manager = Self::new();
+        critical_section::with(|cs| LAST_SWITCH_UPTIME_US.borrow(cs).set(None));
+    }
+
+    /// Returns number of timer interrupts handled by the scheduler.
+    pub fn isr_tick_count() -> u32 {
+        ISR_TICK_COUNT.load(Ordering::Relaxed)
+    }
+
+    /// Returns time of task manager clock in microseconds at the last context switch.
+    /// The clock is the system clock timer reserved by [crate::init_system].
+    /// Returns None before the first context switch or if the port has no timer for the clock
+    /// (on Esp32 with `network` feature both timers are already used by Martos).
+    pub fn last_switch_uptime_us() -> Option<u64> {
+        critical_section::with(|cs| LAST_SWITCH_UPTIME_US.borrow(cs).get())
+    }
+
+    /// Returns number of stalls of the timer interrupt reported by the supervisor.
+    pub fn stall_count() -> u32 {
+        STALL_COUNT.load(Ordering::Relaxed)
+    }
+
+    /// Takes the system clock as clock of task manager.
+    /// Panics, if the port has a clock timer, but it is not reserved, e.g. the system is not initialized
+    /// or the timer was taken before initialization. Without the clock switch times are not recorded.
+    fn start_clock() {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        task_manager.clock_timer = crate::ports::clock_timer();
+        if crate::ports::CLOCK_TIMER.is_some() && task_manager.clock_timer.is_none() {
+            panic!("system clock is not reserved, init_system should be called before task manager is started");
+        }
+    }
+
+    /// Enables supervision of the timer interrupt. Every `check_period` loop iterations of tasks
+    /// the supervisor checks whether the scheduler has handled a timer interrupt since the previous check.
+    /// If it has not, `on_stall` is called once until the interrupt fires again.
+    pub fn on_preemption_stall(check_period: u32, on_stall: fn()) {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        task_manager.stall_supervisor = Some(StallSupervisor {
+            check_period,
+            on_stall,
+            checks: 0,
+            last_tick_count: Self::isr_tick_count(),
+            is_stalled: false,
+        });
+    }
+
+    /// Supervisor check. Is called by threads after every loop iteration.
+    /// Tasks with long loop iterations may call it themselves to detect stalls earlier.
+    pub fn check_preemption() {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        if let Some(supervisor) = task_manager.stall_supervisor.as_mut() {
+            supervisor.checks += 1;
+            if supervisor.checks < supervisor.check_period {
+                return;
+            }
+            supervisor.checks = 0;
+
+            let tick_count = Self::isr_tick_count();
+            if tick_count != supervisor.last_tick_count {
+                supervisor.last_tick_count = tick_count;
+                supervisor.is_stalled = false;
+            } else if !supervisor.is_stalled {
+                supervisor.is_stalled = true;
+                STALL_COUNT.fetch_add(1, Ordering::Relaxed);
+                (supervisor.on_stall)();
+            }
+        }
+    }
+
+    /// Writes states of all threads for diagnostics, one thread per line in the format of cooperative task manager.
+    /// Threads are unnamed, so they are named by their identifiers.
+    pub(crate) fn write_tasks(writer: &mut dyn Write) -> fmt::Result {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        writeln!(writer, "count={}", task_manager.tasks.len())?;
+        writeln!(writer, "current={}", task_manager.task_to_execute_index)?;
+        for index in 0..task_manager.tasks.len() {
+            let task = task_manager.task_info(index);
+            writeln!(
+                writer,
+                "task id={} name=task-{} status={} priority={} flags={}",
+                task.id,
+                task.id,
+                status_name(task.status),
+                task.priority,
+                task.flags.0
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add task to task manager with the specified priority. Returns identifier of the task.
+    /// Priority is truncated to [MAX_TASK_PRIORITY]. Tasks added by [TaskManagerTrait::add_task] have priority 0.
+    ///
+    /// Threads are switched only by the timer interrupt. On every interrupt the scheduler selects
+    /// the next not finished thread with the highest priority, threads of the same priority are
+    /// executed in round-robin order. To avoid starvation a thread, that is passed over, gains
+    /// one level of priority per [AGING_SLICES] time slices. So a thread of priority `p` outranks
+    /// every thread, that does not wait itself, after at most `(MAX_TASK_PRIORITY - p + 1) * AGING_SLICES`
+    /// time slices, even if a thread of higher priority never reaches its stop condition.
+    /// Time slices kept because of [TaskFlags::NO_PREEMPT] are added to this bound.
+    pub fn add_priority_task(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        priority: TaskPriorityType,
+    ) -> TaskIdType {
+        Self::add_thread(
+            setup_fn,
+            loop_fn,
+            stop_condition_fn,
+            priority,
+            THREAD_STACK_SIZE,
+        )
+    }
+
+    /// Add task to task manager with the specified scheduling flags. Returns identifier of the task.
+    ///
+    /// With [TaskFlags::NO_PREEMPT] the timer interrupt does not switch away from the thread,
+    /// even to threads of higher priority, for up to `max_slices` consecutive time slices.
+    /// With [TaskFlags::EXCLUSIVE_BURST] the thread is not switched to threads of the same priority
+    /// for up to `max_slices` consecutive time slices, but threads of higher priority still win.
+    /// After the bound is reached the thread is switched as usual, so a flagged thread can not
+    /// starve other threads.
+    pub fn add_task_with_flags(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        flags: TaskFlags,
+        max_slices: u32,
+    ) -> TaskIdType {
+        let id = Self::add_thread(setup_fn, loop_fn, stop_condition_fn, 0, THREAD_STACK_SIZE);
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let thread = task_manager.tasks.last_mut().unwrap();
+        thread.flags = flags;
+        thread.max_slices = max_slices;
+        id
+    }
+
+    /// Adds periodic tasks and assigns their priorities rate-monotonically within the band:
+    /// the task with the shortest period gets the highest priority of the band, tasks with equal
+    /// periods get equal priorities. If the band has less priorities than distinct periods,
+    /// the tasks with the longest periods share the lowest priority of the band. Priorities are
+    /// truncated to [MAX_TASK_PRIORITY]. Returns identifiers of the tasks in order of the specifications.
+    ///
+    /// Loop function of a periodic task is called once per period, the first time when the task is
+    /// started. Periods are measured by the system clock reserved by [crate::init_system], without
+    /// the clock tasks are not periodic. With `monitor_deadlines` every call of loop function,
+    /// that ends after the next release of the task, is counted, see [PreemptiveTaskManager::deadline_misses].
+    ///
+    /// Rate-monotonic assignment is optimal among fixed priorities only for independent tasks,
+    /// that are preempted at once. Martos switches threads only by the timer interrupt, threads with
+    /// [TaskFlags::NO_PREEMPT] and critical sections block threads of higher priority, and aging lets
+    /// threads of lower priority run ahead of released threads of higher priority. These blocking
+    /// terms are not bounded by the helper, so passing the utilization bound does not guarantee
+    /// that deadlines are met, and deadline monitoring should be used to check the task set.
+    /// Cooperative task manager has no priorities, so it has no such helper.
+    pub fn add_rate_monotonic_set(
+        specs: &[PeriodicSpec],
+        priorities: RangeInclusive<TaskPriorityType>,
+        monitor_deadlines: bool,
+    ) -> Vec<TaskIdType> {
+        let mut order: Vec<usize> = (0..specs.len()).collect();
+        order.sort_by_key(|&index| specs[index].period);
+        let mut ranked_priorities = Vec::from_iter(core::iter::repeat_n(0, specs.len()));
+        let mut priority = *priorities.end();
+        for (rank, &index) in order.iter().enumerate() {
+            if rank > 0 && specs[index].period > specs[order[rank - 1]].period {
+                priority = priority.saturating_sub(1).max(*priorities.start());
+            }
+            ranked_priorities[index] = priority;
+        }
+
+        specs
+            .iter()
+            .zip(ranked_priorities)
+            .map(|(spec, priority)| {
+                let id = Self::add_priority_task(
+                    spec.setup_fn,
+                    spec.loop_fn,
+                    spec.stop_condition_fn,
+                    priority,
+                );
+                let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+                let thread = task_manager.tasks.last_mut().unwrap();
+                thread.period_us = Some(spec.period.as_micros() as u64);
+                thread.monitors_deadlines = monitor_deadlines;
+                id
+            })
+            .collect()
+    }
+
+    /// Puts the thread, that is being executed, to sleep for the specified duration.
+    /// The scheduler does not select the thread until the duration passes, so other threads,
+    /// including threads of lower priority, are executed meanwhile. The thread spins until it is
+    /// switched away. Without the clock it busy-waits like [crate::timer::delay_us].
+    /// Sleeping of periodic thread moves release of its next job. Does nothing outside of threads.
+    pub fn sleep_current_for(duration: Duration) {
+        if Self::current_task_id().is_none() {
+            return;
+        }
+        if Self::release_current_after(duration) {
+            while !Self::is_current_thread_released() {
+                core::hint::spin_loop();
+            }
+        } else {
+            crate::timer::delay_us(duration.as_micros() as u64);
         }
     }
 
+    /// Moves release time of the thread, that is being executed, by the duration from now.
+    /// Returns false if task manager has no clock.
+    fn release_current_after(duration: Duration) -> bool {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let Some(now_us) = task_manager.clock_us() else {
+            return false;
+        };
+        let thread = &mut task_manager.tasks[task_manager.task_to_execute_index];
+        thread.release_us = Some(now_us.saturating_add(duration.as_micros() as u64));
+        true
+    }
+
+    /// Checks whether the thread, that is being executed, is released and may be selected.
+    fn is_current_thread_released() -> bool {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        task_manager.tasks[task_manager.task_to_execute_index].is_ready(task_manager.clock_us())
+    }
+
+    /// Returns number of jobs of the periodic task, that ended after their deadlines,
+    /// or None if there is no such task. It is 0 for tasks without deadline monitoring.
+    pub fn deadline_misses(id: TaskIdType) -> Option<u32> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        task_manager
+            .tasks
+            .iter()
+            .find(|thread| thread.id == id)
+            .map(|thread| thread.deadline_misses)
+    }
+
+    /// Returns time of task manager clock in microseconds or None if task manager has no clock.
+    fn clock_us(&self) -> Option<u64> {
+        self.clock_timer
+            .map(|timer_index| Port::get_time(timer_index).as_micros() as u64)
+    }
+
+    /// Add task to task manager with stack of the specified size in bytes. Returns identifier of the task.
+    /// The size is rounded up to the stack alignment of the platform and should be greater than zero.
+    /// Tasks added by [TaskManagerTrait::add_task] have stack of 1024 bytes.
+    pub fn add_task_with_stack_size(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        stack_size: usize,
+    ) -> TaskIdType {
+        Self::add_thread(setup_fn, loop_fn, stop_condition_fn, 0, stack_size)
+    }
+
+    /// Allocates stack and adds thread with the specified priority. Returns identifier of the task.
+    pub(crate) fn add_thread(
+        setup_fn: TaskSetupFunctionType,
+        loop_fn: TaskLoopFunctionType,
+        stop_condition_fn: TaskStopConditionFunctionType,
+        priority: TaskPriorityType,
+        stack_size: usize,
+    ) -> TaskIdType {
+        assert!(stack_size > 0, "stack size should be greater than zero");
+        let layout = Layout::from_size_align(stack_size, STACK_ALIGN)
+            .unwrap()
+            .pad_to_align();
+        let stack = unsafe { alloc::alloc::alloc(layout) };
+        if stack.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let id = task_manager.next_task_id;
+        task_manager.next_task_id += 1;
+        let mut thread = Thread::new(
+            id,
+            stack,
+            layout,
+            setup_fn,
+            loop_fn,
+            stop_condition_fn,
+            priority.min(MAX_TASK_PRIORITY),
+        );
+        Port::setup_stack(&mut thread);
+        task_manager.tasks.push(thread);
+        id
+    }
+
+    /// Returns state of the task with the specified identifier or None if there is no such task.
+    /// Threads are unnamed, so name of the task is always empty.
+    pub fn get_task_info(id: TaskIdType) -> Option<TaskInfo> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        task_manager
+            .tasks
+            .iter()
+            .position(|thread| thread.id == id)
+            .map(|index| task_manager.task_info(index))
+    }
+
+    /// Returns states of all tasks in order of adding. The result is a copy,
+    /// so it stays valid when threads are switched. Names of the tasks are always empty.
+    pub fn list_tasks() -> Vec<TaskInfo> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        (0..task_manager.tasks.len())
+            .map(|index| task_manager.task_info(index))
+            .collect()
+    }
+
+    /// Returns state of the thread with the specified index.
+    fn task_info(&self, index: usize) -> TaskInfo {
+        let thread = &self.tasks[index];
+        let status = if thread.is_finished {
+            TaskStatus::Finished
+        } else if !self.first_task && index == self.task_to_execute_index {
+            TaskStatus::Running
+        } else {
+            TaskStatus::Ready
+        };
+        TaskInfo {
+            id: thread.id,
+            priority: thread.priority,
+            status,
+            restart_count: 0,
+            flags: thread.flags,
+            name: [0; TASK_NAME_MAX_LEN],
+            inbox_dropped: 0,
+            wake_latency_iterations: 0,
+            boosted_wakes: 0,
+            wake_latency_us: 0,
+        }
+    }
+
+    /// Marks the thread, that is being executed, as finished, so it is removed and its stack is freed
+    /// at context switch. Is called by the thread, when its stop condition is reached, and on termination.
+    pub(crate) fn finish_current_thread() {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let index = task_manager.task_to_execute_index;
+        task_manager.tasks[index].is_finished = true;
+    }
+
+    /// Checks if the thread, that is being executed, is finished or terminated.
+    pub(crate) fn is_current_thread_finished() -> bool {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        task_manager.tasks[task_manager.task_to_execute_index].is_finished
+    }
+
+    /// Removes finished threads and frees their stacks, keeping index of the selected thread valid.
+    /// The interrupted thread is kept, because the interrupt handler may still use its stack,
+    /// so it is removed at the next context switch.
+    fn remove_finished_threads(interrupted_index: Option<usize>) {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let selected_index = task_manager.task_to_execute_index;
+        let mut index = 0;
+        let mut removed_before_selected = 0;
+        task_manager.tasks.retain(|thread| {
+            let keep =
+                !thread.is_finished || Some(index) == interrupted_index || index == selected_index;
+            if !keep {
+                unsafe { alloc::alloc::dealloc(thread.stack, thread.stack_layout) };
+                if index < selected_index {
+                    removed_before_selected += 1;
+                }
+            }
+            index += 1;
+            keep
+        });
+        task_manager.task_to_execute_index = selected_index - removed_before_selected;
+    }
+
+    /// Selects thread to execute: ready thread with the highest effective priority,
+    /// searching in round-robin order after the current thread. On the first call the search starts
+    /// from the first thread. If no thread is ready, the current thread is kept.
+    /// Other ready threads wait one more time slice, waiting of the selected thread is reset.
     fn next_thread() {
-        unsafe {
-            TASK_MANAGER.task_to_execute_index =
-                (TASK_MANAGER.task_to_execute_index + 1) % TASK_MANAGER.tasks.len()
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let now_us = task_manager.clock_us();
+        let count = task_manager.tasks.len();
+        let start = if task_manager.first_task {
+            0
+        } else {
+            task_manager.task_to_execute_index + 1
+        };
+        let mut selected: Option<usize> = None;
+        for offset in 0..count {
+            let index = (start + offset) % count;
+            let thread = &task_manager.tasks[index];
+            if !thread.is_ready(now_us) {
+                continue;
+            }
+            if selected.is_none_or(|selected| {
+                thread.effective_priority() > task_manager.tasks[selected].effective_priority()
+            }) {
+                selected = Some(index);
+            }
         }
+        if let Some(index) = selected {
+            task_manager.task_to_execute_index = index;
+            for (other_index, thread) in task_manager.tasks.iter_mut().enumerate() {
+                if other_index == index {
+                    thread.waited_slices = 0;
+                } else if thread.is_ready(now_us) {
+                    thread.waited_slices = thread.waited_slices.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    /// Checks whether the interrupted thread keeps executing because of its flags.
+    /// Counts consecutive kept time slices and resets the count, when the thread is switched.
+    fn keeps_current_thread() -> bool {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        if task_manager.first_task {
+            return false;
+        }
+        let thread = &task_manager.tasks[task_manager.task_to_execute_index];
+        let keeps = !thread.is_finished
+            && task_manager.kept_slices < thread.max_slices
+            && (thread.flags.contains(TaskFlags::NO_PREEMPT)
+                || (thread.flags.contains(TaskFlags::EXCLUSIVE_BURST)
+                    && !task_manager
+                        .tasks
+                        .iter()
+                        .any(|other| !other.is_finished && other.priority > thread.priority)));
+        if keeps {
+            task_manager.kept_slices += 1;
+        } else {
+            task_manager.kept_slices = 0;
+        }
+        keeps
     }
 
     pub fn schedule(isr_ctx: &mut TrapFrame) {
+        ISR_TICK_COUNT.fetch_add(1, Ordering::Relaxed);
+        if Self::keeps_current_thread() {
+            // Context of the interrupted thread is left in the interrupt frame.
+            return;
+        }
+        if let Some(timer_index) = unsafe { (*addr_of!(TASK_MANAGER)).clock_timer } {
+            let now_us = Port::get_time(timer_index).as_micros() as u64;
+            critical_section::with(|cs| LAST_SWITCH_UPTIME_US.borrow(cs).set(Some(now_us)));
+        }
+        let interrupted_index =
+            unsafe { (!TASK_MANAGER.first_task).then_some(TASK_MANAGER.task_to_execute_index) };
         if unsafe { !TASK_MANAGER.first_task } {
             let task = unsafe {
                 TASK_MANAGER
@@ -83,9 +707,9 @@ impl PreemptiveTaskManager {
             };
             let ctx = &mut task.context;
             Port::save_ctx(ctx, isr_ctx);
-
-            Self::next_thread();
         }
+        Self::next_thread();
+        Self::remove_finished_threads(interrupted_index);
         unsafe { TASK_MANAGER.first_task = false }
 
         let task = unsafe {
@@ -100,21 +724,69 @@ impl PreemptiveTaskManager {
 }
 
 impl TaskManagerTrait for PreemptiveTaskManager {
-    fn add_task(
+    fn add_task_with_id(
         setup_fn: TaskSetupFunctionType,
         loop_fn: TaskLoopFunctionType,
         stop_condition_fn: TaskStopConditionFunctionType,
-    ) {
-        let layout = Layout::from_size_align(THREAD_STACK_SIZE, STACK_ALIGN).unwrap();
-        let stack = unsafe { alloc::alloc::alloc(layout) };
-        let mut thread = Thread::new(stack, setup_fn, loop_fn, stop_condition_fn);
-        Port::setup_stack(&mut thread);
-        unsafe { TASK_MANAGER.tasks.push(thread) }
-        // todo: dealloc
+    ) -> TaskIdType {
+        Self::add_priority_task(setup_fn, loop_fn, stop_condition_fn, 0)
+    }
+
+    fn terminate_task(id: TaskIdType) -> Result<(), TaskError> {
+        let task_manager = unsafe { &mut *addr_of_mut!(TASK_MANAGER) };
+        let thread = task_manager
+            .tasks
+            .iter_mut()
+            .find(|thread| thread.id == id)
+            .ok_or(TaskError::NotFound)?;
+        if thread.is_finished {
+            return Err(TaskError::InvalidState);
+        }
+        thread.cancel_token.cancel();
+        thread.is_finished = true;
+        Ok(())
+    }
+
+    fn terminate_current_task() -> Result<(), TaskError> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        if task_manager.first_task {
+            return Err(TaskError::NotFound);
+        }
+        if Self::is_current_thread_finished() {
+            return Err(TaskError::InvalidState);
+        }
+        task_manager.tasks[task_manager.task_to_execute_index]
+            .cancel_token
+            .cancel();
+        Self::finish_current_thread();
+        Ok(())
+    }
+
+    fn current_task_id() -> Option<TaskIdType> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        if task_manager.first_task {
+            return None;
+        }
+        task_manager
+            .tasks
+            .get(task_manager.task_to_execute_index)
+            .map(|thread| thread.id)
+    }
+
+    fn current_cancel_token() -> Option<CancelToken> {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        if task_manager.first_task {
+            return None;
+        }
+        task_manager
+            .tasks
+            .get(task_manager.task_to_execute_index)
+            .map(|thread| thread.cancel_token.clone())
     }
 
     fn start_task_manager() -> ! {
         // todo!("idle task?");
+        Self::start_clock();
         Port::setup_interrupt();
         loop {}
     }
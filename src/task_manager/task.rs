@@ -19,6 +19,7 @@ pub type TaskStopConditionFunctionType = fn() -> bool;
 pub type TaskStopConditionFunctionType = extern "C" fn() -> bool;
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 /// Task representation for task manager.
 pub struct Task {
     /// Setup function, that is called once at the beginning of task.
@@ -1,18 +1,153 @@
+use alloc::vec::Vec;
+use core::fmt;
 use core::time::Duration;
 
-use crate::ports::{Port, PortTrait};
+use crate::ports::{Port, TimerPort};
+use crate::task_manager::{CancelToken, TaskError};
 
 /// Type for tick counting. It is signed for synchronization. It should be u128.
 pub type TickType = u64;
 
-/// The definition of the timers themselves.
+/// Errors of getting a hardware timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerError {
+    /// Timer with the specified index does not exist.
+    InvalidIndex,
+    /// Timer is already acquired by user.
+    Busy,
+    /// Timer is used by Martos itself. Contains the name of the subsystem owning the timer.
+    ReservedBySystem(&'static str),
+    /// Operation is not allowed while the timer is started.
+    Running,
+    /// Platform does not support the requested timer resolution.
+    UnsupportedResolution,
+}
+
+impl TimerError {
+    /// Returns stable error code of the error. Timer error codes are in range `-100..=-119`.
+    pub fn error_code(&self) -> i32 {
+        match self {
+            TimerError::InvalidIndex => -100,
+            TimerError::Busy => -101,
+            TimerError::ReservedBySystem(_) => -102,
+            TimerError::Running => -103,
+            TimerError::UnsupportedResolution => -104,
+        }
+    }
+}
+
+impl fmt::Display for TimerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimerError::InvalidIndex => write!(f, "timer with this index does not exist"),
+            TimerError::Busy => write!(f, "timer is already acquired"),
+            TimerError::ReservedBySystem(owner) => write!(f, "timer is reserved by {}", owner),
+            TimerError::Running => write!(f, "timer is started"),
+            TimerError::UnsupportedResolution => write!(f, "timer resolution is not supported"),
+        }
+    }
+}
+
+impl core::error::Error for TimerError {}
+
+/// Resolution of a hardware timer, that is length of one timer tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerResolution {
+    /// One tick is 1 µs.
+    Micros1,
+    /// One tick is 100 ns.
+    Nanos100,
+    /// Raw divider of the clock, that drives the timers. Tick length is divider / clock frequency.
+    Divider(u32),
+}
+
+impl TimerResolution {
+    /// Returns divider of the source clock with the specified frequency for the resolution.
+    fn divider(&self, source_clock_hz: u32) -> Result<u32, TimerError> {
+        let ticks_per_second = match self {
+            TimerResolution::Micros1 => 1_000_000,
+            TimerResolution::Nanos100 => 10_000_000,
+            TimerResolution::Divider(divider) => return Ok(*divider),
+        };
+        if source_clock_hz.is_multiple_of(ticks_per_second) {
+            Ok(source_clock_hz / ticks_per_second)
+        } else {
+            Err(TimerError::UnsupportedResolution)
+        }
+    }
+}
+
+/// State of a hardware timer slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerSlotState {
+    /// Timer can be acquired.
+    Free,
+    /// Timer is acquired by user.
+    UserAcquired,
+    /// Timer is used by Martos itself. Contains the name of the subsystem owning the timer.
+    Reserved(&'static str),
+}
+
+/// Information about a hardware timer slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimerSlotInfo {
+    /// Timer number in the timer block.
+    pub index: u8,
+    /// Current state of the timer.
+    pub state: TimerSlotState,
+}
+
+/// Returns states of all hardware timers of the current platform.
+pub fn list_timers() -> Vec<TimerSlotInfo> {
+    (0..Port::timer_count())
+        .map(|index| TimerSlotInfo {
+            index,
+            state: Port::timer_slot_state(index),
+        })
+        .collect()
+}
+
+/// Returns the CPU frequency of the current platform in hertz.
+pub fn cpu_freq_hz() -> u32 {
+    Port::cpu_freq_hz()
+}
+
+/// Busy-waits for the specified number of microseconds.
+/// Blocks the whole task manager during waiting, so it should be used only for short delays.
+pub fn delay_us(us: u64) {
+    Port::delay_us(us);
+}
+
+/// Busy-waits for the specified number of milliseconds.
+/// Blocks the whole task manager during waiting, so it should be used only for short delays.
+pub fn delay_ms(ms: u64) {
+    delay_us(ms.saturating_mul(1000));
+}
+
+/// Busy-waits for the specified number of milliseconds like [delay_ms], but checks the token
+/// every millisecond and returns [TaskError::Cancelled] as soon as it is cancelled.
+pub fn delay_ms_cancellable(ms: u64, token: &CancelToken) -> Result<(), TaskError> {
+    for _ in 0..ms {
+        if token.is_cancelled() {
+            return Err(TaskError::Cancelled);
+        }
+        delay_us(1000);
+    }
+    if token.is_cancelled() {
+        return Err(TaskError::Cancelled);
+    }
+    Ok(())
+}
+
+/// The definition of the timers themselves. The hardware timer is released when the timer is dropped.
+/// Timers are created only by [Timer::get_timer], so every timer owns the hardware timer it refers to.
 /// TODO: Should contain synchronization period and synchronization scale.
 #[repr(C)]
 pub struct Timer {
     /// Timer number in the timer block.
-    pub timer_index: u8,
+    timer_index: u8,
     /// Number of ticks in timer.
-    pub tick_counter: TickType,
+    tick_counter: TickType,
 }
 
 impl Timer {
@@ -22,19 +157,44 @@ impl Timer {
     }
 
     /// Gets the timer instance at the specified index.
-    /// Returns Some timer instance on success.
-    /// Returns None if timer is busy or the specified index is invalid.
-    pub fn get_timer(timer_index: u8) -> Option<Self> {
-        if Port::valid_timer_index(timer_index) && Port::try_acquire_timer(timer_index) {
-            Some(Self {
+    /// Returns timer instance on success.
+    /// Returns an error if the specified index is invalid, the timer is busy or it is reserved by Martos.
+    pub fn get_timer(timer_index: u8) -> Result<Self, TimerError> {
+        if !Port::valid_timer_index(timer_index) {
+            return Err(TimerError::InvalidIndex);
+        }
+        if let TimerSlotState::Reserved(owner) = Port::timer_slot_state(timer_index) {
+            return Err(TimerError::ReservedBySystem(owner));
+        }
+        if Port::try_acquire_timer(timer_index) {
+            Ok(Self {
                 timer_index,
                 tick_counter: 0,
             })
         } else {
-            None
+            Err(TimerError::Busy)
         }
     }
 
+    /// Returns timer that owns no hardware timer. It has invalid index, so dropping it releases nothing.
+    #[cfg(any(feature = "c-library", test))]
+    pub(crate) const fn unacquired() -> Self {
+        Self {
+            timer_index: u8::MAX,
+            tick_counter: 0,
+        }
+    }
+
+    /// Returns number of the timer in the timer block.
+    pub fn timer_index(&self) -> u8 {
+        self.timer_index
+    }
+
+    /// Returns number of ticks counted by [Timer::loop_timer].
+    pub fn tick_counter(&self) -> TickType {
+        self.tick_counter
+    }
+
     /// Starts timer ticking.
     // TODO: What should happen after overflow?
     pub fn loop_timer(&mut self) {
@@ -63,13 +223,46 @@ impl Timer {
         Port::stop_hardware_timer(self.timer_index)
     }
 
+    /// Changes resolution of the timer. Time returned by [Timer::get_time] is rounded down to whole ticks.
+    /// Returns an error if the timer is started or the platform does not support the resolution.
+    pub fn set_resolution(&self, resolution: TimerResolution) -> Result<(), TimerError> {
+        let divider = resolution.divider(Port::timer_source_clock_hz())?;
+        Port::set_timer_divider(self.timer_index, divider)
+    }
+
+    /// Returns length of one tick of the timer, rounded down to nanoseconds.
+    pub fn resolution(&self) -> Duration {
+        let tick_ns = Port::timer_divider(self.timer_index) as u64 * 1_000_000_000
+            / Port::timer_source_clock_hz() as u64;
+        Duration::from_nanos(tick_ns)
+    }
+
     /// Returns current counter value.
     pub fn get_time(&self) -> Duration {
         Port::get_time(self.timer_index)
     }
 
-    /// Releases the hardware timer.
-    pub fn release_timer(&self) {
-        Port::release_hardware_timer(self.timer_index)
+    /// Releases the hardware timer. The timer can not be used after that.
+    pub fn release_timer(self) {
+        drop(self)
+    }
+
+    /// Releases the hardware timer, if it is acquired by user. Timers reserved by Martos
+    /// and invalid indices are ignored, so the timer can not free a timer it does not own.
+    pub(crate) fn release_hardware_timer(&self) {
+        if Port::valid_timer_index(self.timer_index)
+            && !matches!(
+                Port::timer_slot_state(self.timer_index),
+                TimerSlotState::Reserved(_)
+            )
+        {
+            Port::release_hardware_timer(self.timer_index)
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.release_hardware_timer()
     }
 }
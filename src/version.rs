@@ -0,0 +1,115 @@
+use core::cell::Cell;
+use core::fmt;
+
+// Declare version_tests file as child file to test private functions.
+#[cfg(test)]
+#[path = "../tests/version/version_tests.rs"]
+mod version_tests;
+
+// Build information generated by build.rs.
+include!(concat!(env!("OUT_DIR"), "/version_info.rs"));
+
+/// Version of C ABI of Martos. It is increased whenever any exported function signature
+/// or layout of any type passed through C API changes.
+pub const MARTOS_ABI_VERSION: u32 = 8;
+
+/// Information about Martos build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// Version of Martos crate.
+    pub crate_version: &'static str,
+    /// Short git hash of Martos sources or "unknown" if it is not available.
+    pub git_hash: &'static str,
+    /// Enabled cargo features.
+    pub build_features: &'static [&'static str],
+    /// Target triple Martos is built for.
+    pub target: &'static str,
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "martos {} ({}) target={} features=",
+            self.crate_version, self.git_hash, self.target
+        )?;
+        for (index, feature) in self.build_features.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(feature)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns information about Martos build.
+pub fn version() -> VersionInfo {
+    VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        git_hash: GIT_HASH,
+        build_features: BUILD_FEATURES,
+        target: TARGET,
+    }
+}
+
+/// Type of function, that logs information about Martos build.
+pub type VersionLoggerType = fn(&VersionInfo);
+
+/// Function, that is called by [crate::init_system] to log information about Martos build.
+static VERSION_LOGGER: critical_section::Mutex<Cell<Option<VersionLoggerType>>> =
+    critical_section::Mutex::new(Cell::new(None));
+
+/// Sets function, that logs information about Martos build. Martos has no output of its own,
+/// so the function is called by [crate::init_system] once per initialization, when it is set,
+/// and may print the version, git hash and features to any output of the platform.
+/// None disables logging.
+pub fn set_version_logger(logger: Option<VersionLoggerType>) {
+    critical_section::with(|cs| VERSION_LOGGER.borrow(cs).set(logger));
+}
+
+/// Passes information about Martos build to the version logger, if it is set.
+pub(crate) fn log_version() {
+    if let Some(logger) = critical_section::with(|cs| VERSION_LOGGER.borrow(cs).get()) {
+        logger(&version());
+    }
+}
+
+/// Writer to a byte buffer, that silently truncates the output and counts its full length.
+struct TruncatingWriter<'a> {
+    /// Buffer to write to.
+    buf: &'a mut [u8],
+    /// Full length of the output.
+    len: usize,
+}
+
+impl fmt::Write for TruncatingWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            if let Some(slot) = self.buf.get_mut(self.len) {
+                *slot = byte;
+            }
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Writes version string to the buffer as NUL-terminated C string, truncating it if necessary.
+/// Returns length of the full version string without NUL, like snprintf.
+#[cfg_attr(not(feature = "c-library"), allow(dead_code))]
+pub(crate) fn write_version_string(buf: &mut [u8]) -> usize {
+    use core::fmt::Write;
+
+    let capacity = buf.len().saturating_sub(1);
+    let mut writer = TruncatingWriter {
+        buf: &mut buf[..capacity],
+        len: 0,
+    };
+    let _ = write!(writer, "{}", version());
+    let len = writer.len;
+    if let Some(nul) = buf.get_mut(len.min(capacity)) {
+        *nul = 0;
+    }
+    len
+}
@@ -0,0 +1,318 @@
+mod c_header_tests {
+    #[cfg(feature = "c-library")]
+    use martos::c_api::{DurationFFI, TimerOption};
+    use martos::error::MartosError;
+    use martos::error::SystemError;
+    use martos::task_manager::{InboxError, InboxMessage, TaskError, TaskIdType, TaskInfo};
+    use martos::timer::{TickType, Timer, TimerError};
+    use martos::MARTOS_ABI_VERSION;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::mem::{align_of, size_of};
+    use std::path::Path;
+
+    /// C ABI, that is recorded for MARTOS_ABI_VERSION: fingerprint of martos.h declarations
+    /// and host layouts (name, size, align) of types passed through C API.
+    /// If test_abi_version fails, bump MARTOS_ABI_VERSION and record the new values here.
+    const RECORDED_ABI: (u32, u64, &[(&str, usize, usize)]) = (
+        8,
+        0xf196_c8f0_c918_1bd7,
+        &[
+            ("TickType", 8, 8),
+            ("TaskIdType", 8, 8),
+            ("Timer", 16, 8),
+            ("InboxMessage", 12, 4),
+            ("TaskInfo", 56, 8),
+            ("DurationFFI", 16, 8),
+            ("TimerOption", 24, 8),
+        ],
+    );
+
+    /// Types defined in C API module. Their layouts are checked only with "c-library" feature.
+    const C_API_TYPES: &[&str] = &["DurationFFI", "TimerOption"];
+
+    /// Reads file relative to the crate root.
+    fn read_crate_file(path: &str) -> String {
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+    }
+
+    /// Returns names of functions marked with #[no_mangle] in the source.
+    fn exported_functions(source: &str) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        let mut is_no_mangle = false;
+        for line in source.lines().map(str::trim) {
+            if line == "#[no_mangle]" {
+                is_no_mangle = true;
+            } else if is_no_mangle && line.contains("extern \"C\" fn ") {
+                let name = line.split("fn ").nth(1).unwrap().split('(').next().unwrap();
+                names.insert(name.to_string());
+                is_no_mangle = false;
+            }
+        }
+        names
+    }
+
+    /// Returns names of functions declared in the header.
+    fn declared_functions(header: &str) -> BTreeSet<String> {
+        header
+            .lines()
+            .filter(|line| {
+                !line.starts_with(['#', '/', ' ', '}'])
+                    && !line.starts_with("typedef")
+                    && !line.starts_with("extern")
+                    && line.ends_with(");")
+            })
+            .map(|line| {
+                let prefix = line.split('(').next().unwrap();
+                prefix.rsplit([' ', '*']).next().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    /// Returns FNV-1a hash of declarations in the header. Comments and the ABI version macro are ignored.
+    fn header_fingerprint(header: &str) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for line in header.lines().map(str::trim) {
+            if line.is_empty()
+                || line.starts_with("/*")
+                || line.starts_with('*')
+                || line.starts_with("#define MARTOS_ABI_VERSION ")
+            {
+                continue;
+            }
+            for byte in line.bytes().chain([b'\n']) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    /// Returns value of enum constant in the header.
+    fn header_constant(header: &str, name: &str) -> i32 {
+        let line = header
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with(&format!("{} = ", name)))
+            .unwrap_or_else(|| panic!("{} is not declared in martos.h", name));
+        line.split(" = ")
+            .nth(1)
+            .unwrap()
+            .trim_end_matches(',')
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    /// Tests if martos.h declares exactly the functions exported by C API.
+    fn test_header_declares_exported_functions() {
+        let exported = exported_functions(&read_crate_file("src/c_api.rs"));
+        let declared = declared_functions(&read_crate_file("c-library/martos.h"));
+        assert!(!exported.is_empty());
+        assert_eq!(
+            exported, declared,
+            "martos.h is out of sync with src/c_api.rs"
+        );
+    }
+
+    #[test]
+    /// Tests if martos_freertos.h declares exactly the functions exported by FreeRTOS compatibility layer.
+    fn test_freertos_header_declares_exported_functions() {
+        let exported = exported_functions(&read_crate_file("src/freertos_compat.rs"));
+        let declared = declared_functions(&read_crate_file("c-library/martos_freertos.h"));
+        assert!(!exported.is_empty());
+        assert_eq!(
+            exported, declared,
+            "martos_freertos.h is out of sync with src/freertos_compat.rs"
+        );
+    }
+
+    /// Returns value of integer macro in the header.
+    #[cfg(feature = "freertos-compat")]
+    fn header_define(header: &str, name: &str) -> i64 {
+        let line = header
+            .lines()
+            .find(|line| line.starts_with(&format!("#define {} ", name)))
+            .unwrap_or_else(|| panic!("{} is not defined in the header", name));
+        line.split(' ')
+            .nth(2)
+            .unwrap()
+            .trim_matches(['(', ')'])
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "freertos-compat")]
+    /// Tests that results and tick rate of martos_freertos.h match FreeRTOS compatibility layer.
+    fn test_freertos_header_constants() {
+        use martos::freertos_compat::*;
+        let header = read_crate_file("c-library/martos_freertos.h");
+        let constants = [
+            ("pdPASS", PD_PASS),
+            ("pdFAIL", PD_FAIL),
+            ("pdTRUE", PD_TRUE),
+            ("pdFALSE", PD_FALSE),
+            ("errQUEUE_FULL", ERR_QUEUE_FULL),
+            ("errQUEUE_EMPTY", ERR_QUEUE_EMPTY),
+            (
+                "errCOULD_NOT_ALLOCATE_REQUIRED_MEMORY",
+                ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY,
+            ),
+            ("errMARTOS_UNSUPPORTED", ERR_UNSUPPORTED),
+        ];
+        for (name, value) in constants {
+            assert_eq!(header_define(&header, name), value as i64, "{}", name);
+        }
+        assert_eq!(
+            header_define(&header, "configTICK_RATE_HZ"),
+            TICK_RATE_HZ as i64
+        );
+        assert!(header.contains("#define portMAX_DELAY ((TickType_t)0xffffffffUL)\n"));
+        assert_eq!(PORT_MAX_DELAY, 0xffff_ffff);
+    }
+
+    /// All Martos errors.
+    const ERRORS: &[MartosError] = &[
+        MartosError::System(SystemError::ReinitUnsupported),
+        MartosError::System(SystemError::AbiMismatch),
+        MartosError::Timer(TimerError::InvalidIndex),
+        MartosError::Timer(TimerError::Busy),
+        MartosError::Timer(TimerError::ReservedBySystem("")),
+        MartosError::Timer(TimerError::Running),
+        MartosError::Timer(TimerError::UnsupportedResolution),
+        MartosError::Inbox(InboxError::NoTask),
+        MartosError::Inbox(InboxError::NoInbox),
+        MartosError::Inbox(InboxError::Full),
+        MartosError::Inbox(InboxError::TaskFinished),
+        MartosError::Task(TaskError::NotFound),
+        MartosError::Task(TaskError::InvalidState),
+        MartosError::Task(TaskError::CurrentlyRunning),
+        MartosError::Task(TaskError::ClockUnavailable),
+        MartosError::Task(TaskError::Cancelled),
+    ];
+
+    /// Returns name of the header constant for the error.
+    /// The match is exhaustive, so a new error does not compile until it gets a name.
+    fn header_name(error: &MartosError) -> &'static str {
+        match error {
+            MartosError::System(error) => match error {
+                SystemError::ReinitUnsupported => "MARTOS_SYSTEM_REINIT_UNSUPPORTED",
+                SystemError::AbiMismatch => "MARTOS_SYSTEM_ABI_MISMATCH",
+            },
+            MartosError::Timer(error) => match error {
+                TimerError::InvalidIndex => "MARTOS_TIMER_INVALID_INDEX",
+                TimerError::Busy => "MARTOS_TIMER_BUSY",
+                TimerError::ReservedBySystem(_) => "MARTOS_TIMER_RESERVED_BY_SYSTEM",
+                TimerError::Running => "MARTOS_TIMER_RUNNING",
+                TimerError::UnsupportedResolution => "MARTOS_TIMER_UNSUPPORTED_RESOLUTION",
+            },
+            MartosError::Inbox(error) => match error {
+                InboxError::NoTask => "MARTOS_INBOX_NO_TASK",
+                InboxError::NoInbox => "MARTOS_INBOX_NO_INBOX",
+                InboxError::Full => "MARTOS_INBOX_FULL",
+                InboxError::TaskFinished => "MARTOS_INBOX_TASK_FINISHED",
+            },
+            MartosError::Task(error) => match error {
+                TaskError::NotFound => "MARTOS_TASK_NOT_FOUND",
+                TaskError::InvalidState => "MARTOS_TASK_INVALID_STATE",
+                TaskError::CurrentlyRunning => "MARTOS_TASK_CURRENTLY_RUNNING",
+                TaskError::ClockUnavailable => "MARTOS_TASK_CLOCK_UNAVAILABLE",
+                TaskError::Cancelled => "MARTOS_TASK_CANCELLED",
+            },
+        }
+    }
+
+    #[test]
+    /// Tests if every error code of Martos errors has a matching constant in martos.h
+    /// and the header has no other error constants.
+    fn test_header_error_codes() {
+        let header = read_crate_file("c-library/martos.h");
+        for error in ERRORS {
+            let name = header_name(error);
+            assert_eq!(
+                header_constant(&header, name),
+                error.error_code(),
+                "{}",
+                name
+            );
+        }
+        let names: BTreeSet<&str> = ERRORS.iter().map(header_name).collect();
+        assert_eq!(names.len(), ERRORS.len());
+        let declared = header
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("MARTOS_") && line.contains(" = "))
+            .count();
+        assert_eq!(declared, ERRORS.len(), "martos.h has unknown error codes");
+    }
+
+    #[test]
+    #[cfg(feature = "c-library")]
+    /// Tests that unsupported iteration value of martos.h matches C API and is returned by preemptive task manager.
+    fn test_iteration_unsupported() {
+        use martos::c_api::{run_one_iteration, ITERATION_UNSUPPORTED};
+        let header = read_crate_file("c-library/martos.h");
+        assert!(header.contains("#define MARTOS_ITERATION_UNSUPPORTED (UINT64_MAX - 1)\n"));
+        assert_eq!(ITERATION_UNSUPPORTED, u64::MAX - 1);
+        if cfg!(feature = "preemptive") {
+            assert_eq!(run_one_iteration(), ITERATION_UNSUPPORTED);
+        }
+    }
+
+    #[test]
+    /// Tests that C ABI does not change without increasing MARTOS_ABI_VERSION.
+    fn test_abi_version() {
+        let header = read_crate_file("c-library/martos.h");
+        assert!(header.contains(&format!(
+            "#define MARTOS_ABI_VERSION {}\n",
+            MARTOS_ABI_VERSION
+        )));
+        let layouts = [
+            ("TickType", size_of::<TickType>(), align_of::<TickType>()),
+            (
+                "TaskIdType",
+                size_of::<TaskIdType>(),
+                align_of::<TaskIdType>(),
+            ),
+            ("Timer", size_of::<Timer>(), align_of::<Timer>()),
+            (
+                "InboxMessage",
+                size_of::<InboxMessage>(),
+                align_of::<InboxMessage>(),
+            ),
+            ("TaskInfo", size_of::<TaskInfo>(), align_of::<TaskInfo>()),
+            #[cfg(feature = "c-library")]
+            (
+                "DurationFFI",
+                size_of::<DurationFFI>(),
+                align_of::<DurationFFI>(),
+            ),
+            #[cfg(feature = "c-library")]
+            (
+                "TimerOption",
+                size_of::<TimerOption>(),
+                align_of::<TimerOption>(),
+            ),
+        ];
+        let (recorded_version, recorded_fingerprint, recorded_layouts) = RECORDED_ABI;
+        let recorded_layouts: Vec<(&str, usize, usize)> = recorded_layouts
+            .iter()
+            .filter(|(name, _, _)| cfg!(feature = "c-library") || !C_API_TYPES.contains(name))
+            .copied()
+            .collect();
+        assert_eq!(
+            (
+                MARTOS_ABI_VERSION,
+                header_fingerprint(&header),
+                &layouts[..]
+            ),
+            (
+                recorded_version,
+                recorded_fingerprint,
+                &recorded_layouts[..]
+            ),
+            "C ABI is changed: bump MARTOS_ABI_VERSION and update RECORDED_ABI"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+#[cfg(test)]
+mod scheduler_tests {
+    use super::super::*;
+    extern crate std;
+
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use sequential_test::sequential;
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    #[test]
+    /// Tests that long names from C API are truncated on character boundary.
+    fn test_task_name_from_bytes() {
+        assert_eq!(TaskName::from_bytes(b"sensor").as_str(), "sensor");
+        assert_eq!(
+            TaskName::from_bytes(b"a-very-long-task-name").as_str(),
+            "a-very-long-task"
+        );
+        // "ж" takes two bytes, so the 16-byte limit falls in the middle of the last character.
+        assert_eq!(
+            TaskName::from_bytes("abcdefghijklmnoж".as_bytes()).as_str(),
+            "abcdefghijklmno"
+        );
+        assert_eq!(TaskName::from_bytes(b"").as_str(), "");
+    }
+
+    #[test]
+    /// Tests that deadlines of sleeping tasks are compared correctly when the clock wraps.
+    fn test_time_until_wrapping_clock() {
+        assert_eq!(time_until(10_000, 4_000), Some(6_000));
+        assert_eq!(time_until(10_000, 10_000), None);
+        assert_eq!(time_until(10_000, 12_000), None);
+        let now_us = u64::MAX - 5;
+        let deadline_us = now_us.wrapping_add(10);
+        assert_eq!(time_until(deadline_us, now_us), Some(10));
+        assert_eq!(time_until(deadline_us, now_us.wrapping_add(4)), Some(6));
+        assert_eq!(time_until(deadline_us, now_us.wrapping_add(10)), None);
+        assert_eq!(time_until(deadline_us, now_us.wrapping_add(11)), None);
+        assert_eq!(time_until(MAX_SLEEP_US, 0), Some(MAX_SLEEP_US));
+    }
+
+    /// Counter of loop calls for test_loop_adds_tasks.
+    static TEST_LOOP_ADDS_TASKS_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of task vector reallocations caused by loop function for test_loop_adds_tasks.
+    static TEST_LOOP_ADDS_TASKS_REALLOCATIONS: AtomicU32 = AtomicU32::new(0);
+    /// Number of exit hook calls for test_loop_adds_tasks.
+    static TEST_LOOP_ADDS_TASKS_EXITS: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for tasks for test_loop_adds_tasks.
+    fn test_loop_adds_tasks_setup_fn() {}
+    /// Loop function for test_loop_adds_tasks. Adds tasks until task vector is reallocated.
+    fn test_loop_adds_tasks_loop_fn() {
+        TEST_LOOP_ADDS_TASKS_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let capacity = with_task_manager(|task_manager| task_manager.tasks.capacity());
+        loop {
+            CooperativeTaskManager::add_task(
+                test_loop_adds_tasks_setup_fn,
+                test_loop_adds_tasks_added_loop_fn,
+                test_loop_adds_tasks_added_stop_condition_fn,
+            );
+            if with_task_manager(|task_manager| task_manager.tasks.capacity()) != capacity {
+                break;
+            }
+        }
+        TEST_LOOP_ADDS_TASKS_REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for test_loop_adds_tasks.
+    fn test_loop_adds_tasks_stop_condition_fn() -> bool {
+        TEST_LOOP_ADDS_TASKS_COUNTER.load(Ordering::Relaxed) >= 3
+    }
+    /// Loop function for added tasks for test_loop_adds_tasks.
+    fn test_loop_adds_tasks_added_loop_fn() {}
+    /// Stop function for added tasks for test_loop_adds_tasks.
+    fn test_loop_adds_tasks_added_stop_condition_fn() -> bool {
+        true
+    }
+    /// Exit hook for test_loop_adds_tasks.
+    fn test_loop_adds_tasks_exit_hook(_id: TaskIdType, reason: ExitReason) {
+        assert_eq!(reason, ExitReason::StopCondition);
+        TEST_LOOP_ADDS_TASKS_EXITS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that loop function, which reallocates task vector by adding tasks, does not corrupt state of its task.
+    fn test_loop_adds_tasks() {
+        let id = CooperativeTaskManager::add_restartable_task(
+            test_loop_adds_tasks_setup_fn,
+            test_loop_adds_tasks_loop_fn,
+            test_loop_adds_tasks_stop_condition_fn,
+            RestartPolicy::Never,
+        );
+        CooperativeTaskManager::set_exit_hook(id, test_loop_adds_tasks_exit_hook);
+        CooperativeTaskManager::test_start_task_manager();
+
+        assert_eq!(TEST_LOOP_ADDS_TASKS_COUNTER.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            TEST_LOOP_ADDS_TASKS_REALLOCATIONS.load(Ordering::Relaxed),
+            3
+        );
+        assert_eq!(TEST_LOOP_ADDS_TASKS_EXITS.load(Ordering::Relaxed), 1);
+
+        with_task_manager(|task_manager| {
+            let task = task_manager.find_task(id).unwrap();
+            assert!(task.is_finished);
+            assert_eq!(task.restart_count, 0);
+        });
+    }
+
+    /// Trace of task function calls for test_run_one_iteration: task number and function.
+    static TEST_RUN_ONE_ITERATION_TRACE: Mutex<Vec<(u8, char)>> = Mutex::new(Vec::new());
+    /// Setup function for the first task for test_run_one_iteration.
+    fn test_run_one_iteration_setup_fn1() {
+        TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().push((1, 's'));
+    }
+    /// Loop function for the first task for test_run_one_iteration.
+    fn test_run_one_iteration_loop_fn1() {
+        TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().push((1, 'l'));
+    }
+    /// Stop function for the first task for test_run_one_iteration.
+    fn test_run_one_iteration_stop_condition_fn1() -> bool {
+        TEST_RUN_ONE_ITERATION_TRACE
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| **call == (1, 'l'))
+            .count()
+            >= 2
+    }
+    /// Setup function for the second task for test_run_one_iteration.
+    fn test_run_one_iteration_setup_fn2() {
+        TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().push((2, 's'));
+    }
+    /// Loop function for the second task for test_run_one_iteration.
+    fn test_run_one_iteration_loop_fn2() {
+        TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().push((2, 'l'));
+    }
+    /// Stop function for the second task for test_run_one_iteration.
+    fn test_run_one_iteration_stop_condition_fn2() -> bool {
+        TEST_RUN_ONE_ITERATION_TRACE
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| **call == (2, 'l'))
+            .count()
+            >= 4
+    }
+    /// Creates empty task manager with tasks for test_run_one_iteration.
+    fn test_run_one_iteration_reset() {
+        CooperativeTaskManager::reset();
+        TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().clear();
+        CooperativeTaskManager::add_task(
+            test_run_one_iteration_setup_fn1,
+            test_run_one_iteration_loop_fn1,
+            test_run_one_iteration_stop_condition_fn1,
+        );
+        CooperativeTaskManager::add_task(
+            test_run_one_iteration_setup_fn2,
+            test_run_one_iteration_loop_fn2,
+            test_run_one_iteration_stop_condition_fn2,
+        );
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that task manager driven by run_one_iteration calls the same task functions
+    /// in the same order as its own loop and reports when it becomes idle.
+    fn test_run_one_iteration() {
+        test_run_one_iteration_reset();
+        CooperativeTaskManager::test_start_task_manager();
+        let expected = TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap().clone();
+
+        test_run_one_iteration_reset();
+        let mut iterations = 0;
+        loop {
+            let outcome = CooperativeTaskManager::run_one_iteration();
+            iterations += 1;
+            assert!(iterations < 1000);
+            if outcome.next_deadline_us.is_none() {
+                break;
+            }
+            assert_eq!(outcome.next_deadline_us, Some(0));
+        }
+        assert_eq!(*TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap(), expected);
+        assert_eq!(expected.iter().filter(|call| call.1 == 'l').count(), 2 + 4);
+
+        let outcome = CooperativeTaskManager::run_one_iteration();
+        assert!(!outcome.task_ran);
+        assert_eq!(outcome.next_deadline_us, None);
+        assert_eq!(*TEST_RUN_ONE_ITERATION_TRACE.lock().unwrap(), expected);
+    }
+
+    /// Setup function for test_named_task_restarts.
+    fn test_named_task_restarts_setup_fn() {}
+    /// Loop function for test_named_task_restarts.
+    fn test_named_task_restarts_loop_fn() {}
+    /// Stop function for test_named_task_restarts. Task stops after every iteration.
+    fn test_named_task_restarts_stop_condition_fn() -> bool {
+        true
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that name of the task survives its restarts.
+    fn test_named_task_restarts() {
+        CooperativeTaskManager::reset();
+        let id = CooperativeTaskManager::add_named_task(
+            "restarted",
+            test_named_task_restarts_setup_fn,
+            test_named_task_restarts_loop_fn,
+            test_named_task_restarts_stop_condition_fn,
+        );
+        // There is no public API for named restartable tasks, so the policy is set directly.
+        with_task_manager(|task_manager| {
+            task_manager.find_task(id).unwrap().restart_policy = RestartPolicy::MaxTimes(2)
+        });
+        let mut iterations = 0;
+        while CooperativeTaskManager::run_one_iteration().task_ran {
+            iterations += 1;
+            assert!(iterations < 100);
+        }
+
+        let info = CooperativeTaskManager::get_task_info(id).unwrap();
+        assert_eq!(info.restart_count, 2);
+        assert_eq!(info.name(), Some("restarted"));
+        assert_eq!(
+            CooperativeTaskManager::find_task_by_name("restarted"),
+            Some(id)
+        );
+        CooperativeTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that only the first duplicated task name is reported by the diagnostics dump.
+    fn test_duplicate_name_warning() {
+        CooperativeTaskManager::reset();
+        for name in ["sensor", "sensor", "logger", "logger", "sensor"] {
+            CooperativeTaskManager::add_named_task(
+                name,
+                test_named_task_restarts_setup_fn,
+                test_named_task_restarts_loop_fn,
+                test_named_task_restarts_stop_condition_fn,
+            );
+        }
+        let mut dump = std::string::String::new();
+        CooperativeTaskManager::write_tasks(&mut dump).unwrap();
+        let warnings: Vec<&str> = dump
+            .lines()
+            .filter(|line| line.starts_with("warning="))
+            .collect();
+        assert_eq!(warnings, ["warning=duplicate_name name=sensor"]);
+
+        CooperativeTaskManager::reset();
+        let mut dump = std::string::String::new();
+        CooperativeTaskManager::write_tasks(&mut dump).unwrap();
+        assert!(!dump.contains("warning="));
+    }
+}
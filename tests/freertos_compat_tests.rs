@@ -0,0 +1,222 @@
+#[cfg(all(test, feature = "freertos-compat", not(feature = "preemptive")))]
+mod harness;
+
+#[cfg(all(test, feature = "freertos-compat", not(feature = "preemptive")))]
+mod freertos_compat_tests {
+    use crate::harness::TestHarness;
+    use martos::freertos_compat::*;
+    use martos::task_manager::{TaskIdType, TaskManager};
+    use martos::timer::delay_us;
+    use sequential_test::sequential;
+    use std::ffi::{c_char, c_void, CStr};
+    use std::ptr::null_mut;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    /// Parameter of the task for test_task_delay.
+    static TEST_TASK_DELAY_PARAMETER: u32 = 42;
+    /// Tick counts at the beginnings of the task iterations for test_task_delay.
+    static TEST_TASK_DELAY_TICKS: Mutex<Vec<FreeRtosTickType>> = Mutex::new(Vec::new());
+    /// Task function for test_task_delay.
+    extern "C" fn test_task_delay_task(parameters: *mut c_void) {
+        assert_eq!(unsafe { *(parameters as *const u32) }, 42);
+        TEST_TASK_DELAY_TICKS
+            .lock()
+            .unwrap()
+            .push(xTaskGetTickCount());
+        vTaskDelay(ticks_for_ms(10));
+    }
+
+    /// Returns number of ticks in milliseconds like pdMS_TO_TICKS macro of martos_freertos.h.
+    fn ticks_for_ms(ms: u64) -> FreeRtosTickType {
+        (ms * TICK_RATE_HZ as u64 / 1000) as FreeRtosTickType
+    }
+
+    /// Runs iterations of task manager until the condition is met. Virtual clock is advanced
+    /// only by delays, so waiting for sleeping tasks is simulated by delaying until the next deadline.
+    fn run_until(condition: impl Fn() -> bool) {
+        for _ in 0..1000 {
+            if condition() {
+                return;
+            }
+            let outcome = TaskManager::run_one_iteration();
+            delay_us(outcome.next_deadline_us.unwrap_or(0));
+        }
+        panic!("condition is not met");
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that created task gets its parameters and name
+    /// and is delayed for the exact number of ticks on the virtual clock.
+    fn test_task_delay() {
+        let harness = TestHarness::new();
+        let mut handle = null_mut();
+        let result = unsafe {
+            xTaskCreate(
+                test_task_delay_task,
+                c"delayed".as_ptr(),
+                128,
+                &TEST_TASK_DELAY_PARAMETER as *const u32 as *mut c_void,
+                3,
+                &mut handle,
+            )
+        };
+        assert_eq!(result, PD_PASS);
+        let id = handle as TaskIdType;
+        assert_eq!(TaskManager::find_task_by_name("delayed"), Some(id));
+
+        run_until(|| TEST_TASK_DELAY_TICKS.lock().unwrap().len() == 5);
+        let ticks = TEST_TASK_DELAY_TICKS.lock().unwrap().clone();
+        for pair in ticks.windows(2) {
+            assert_eq!(pair[1] - pair[0], 10);
+        }
+        assert_eq!(ticks_to_duration(10).as_millis(), 10);
+
+        vTaskDelete(handle);
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Number of iterations of the task for test_task_delete_self.
+    static TEST_TASK_DELETE_SELF_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Task function for test_task_delete_self, that deletes its task on the third iteration.
+    extern "C" fn test_task_delete_self_task(_parameters: *mut c_void) {
+        if TEST_TASK_DELETE_SELF_COUNTER.fetch_add(1, Ordering::Relaxed) == 2 {
+            vTaskDelete(null_mut());
+        }
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that task deleting itself is not executed anymore and zero stack depth is rejected.
+    fn test_task_delete_self() {
+        let harness = TestHarness::new();
+        let result = unsafe {
+            xTaskCreate(
+                test_task_delete_self_task,
+                std::ptr::null(),
+                128,
+                null_mut(),
+                0,
+                null_mut(),
+            )
+        };
+        assert_eq!(result, PD_PASS);
+        let result = unsafe {
+            xTaskCreate(
+                test_task_delete_self_task,
+                std::ptr::null(),
+                0,
+                null_mut(),
+                0,
+                null_mut(),
+            )
+        };
+        assert_eq!(result, ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY);
+
+        TaskManager::test_start_task_manager();
+        assert_eq!(TEST_TASK_DELETE_SELF_COUNTER.load(Ordering::Relaxed), 3);
+        assert!(harness.finish().is_clean());
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that queue keeps the order of items, reports full and empty states
+    /// and does not wait on cooperative task manager.
+    fn test_queue() {
+        let harness = TestHarness::new();
+        assert!(xQueueCreate(0, 4).is_null());
+        let queue = xQueueCreate(2, 4);
+        let send = |value: u32, to_front: bool| unsafe {
+            let item = &value as *const u32 as *const c_void;
+            if to_front {
+                xQueueSendToFront(queue, item, PORT_MAX_DELAY)
+            } else {
+                xQueueSend(queue, item, PORT_MAX_DELAY)
+            }
+        };
+        let receive = || unsafe {
+            let mut value = 0u32;
+            let result = xQueueReceive(queue, &mut value as *mut u32 as *mut c_void, 10);
+            (result, value)
+        };
+
+        assert_eq!(send(1, false), PD_PASS);
+        assert_eq!(send(2, false), PD_PASS);
+        assert_eq!(send(3, false), ERR_QUEUE_FULL);
+        assert_eq!(unsafe { uxQueueMessagesWaiting(queue) }, 2);
+        assert_eq!(receive(), (PD_PASS, 1));
+        assert_eq!(send(0, true), PD_PASS);
+        assert_eq!(receive(), (PD_PASS, 0));
+        assert_eq!(receive(), (PD_PASS, 2));
+        assert_eq!(receive().0, ERR_QUEUE_EMPTY);
+
+        let mut woken = PD_TRUE;
+        let value = 5u32;
+        let result =
+            unsafe { xQueueSendFromISR(queue, &value as *const u32 as *const c_void, &mut woken) };
+        assert_eq!((result, woken), (PD_PASS, PD_FALSE));
+        assert_eq!(receive(), (PD_PASS, 5));
+        unsafe { vQueueDelete(queue) };
+        harness.finish();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that binary semaphore is created taken and can be given only once before it is taken.
+    fn test_binary_semaphore() {
+        let harness = TestHarness::new();
+        let semaphore = xSemaphoreCreateBinary();
+        unsafe {
+            assert_eq!(xSemaphoreTake(semaphore, PORT_MAX_DELAY), PD_FALSE);
+            assert_eq!(xSemaphoreGive(semaphore), PD_TRUE);
+            assert_eq!(xSemaphoreGive(semaphore), PD_FALSE);
+            assert_eq!(xSemaphoreTake(semaphore, 0), PD_TRUE);
+            let mut woken = PD_TRUE;
+            assert_eq!(xSemaphoreGiveFromISR(semaphore, &mut woken), PD_TRUE);
+            assert_eq!(woken, PD_FALSE);
+            assert_eq!(xSemaphoreTake(semaphore, 0), PD_TRUE);
+            vSemaphoreDelete(semaphore);
+        }
+        harness.finish();
+    }
+
+    /// Names of unsupported functions reported for test_unsupported_functions.
+    static TEST_UNSUPPORTED_FUNCTIONS_NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    /// Unsupported hook for test_unsupported_functions.
+    extern "C" fn test_unsupported_functions_hook(name: *const c_char) {
+        let name = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        TEST_UNSUPPORTED_FUNCTIONS_NAMES.lock().unwrap().push(name);
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that unsupported functions return distinct error and are reported once after the hook is set.
+    fn test_unsupported_functions() {
+        martos_freertos_set_unsupported_hook(Some(test_unsupported_functions_hook));
+        for _ in 0..2 {
+            assert_eq!(xTaskNotifyGive(null_mut()), ERR_UNSUPPORTED);
+            assert_eq!(xTaskNotify(null_mut(), 1, 0), ERR_UNSUPPORTED);
+            assert_eq!(
+                xTaskNotifyWait(0, 0, null_mut(), PORT_MAX_DELAY),
+                ERR_UNSUPPORTED
+            );
+        }
+        assert_eq!(
+            *TEST_UNSUPPORTED_FUNCTIONS_NAMES.lock().unwrap(),
+            ["xTaskNotifyGive", "xTaskNotify", "xTaskNotifyWait"]
+        );
+        assert!(
+            ![PD_PASS, PD_FAIL, ERR_COULD_NOT_ALLOCATE_REQUIRED_MEMORY].contains(&ERR_UNSUPPORTED)
+        );
+
+        // Setting the hook again reports the functions again.
+        martos_freertos_set_unsupported_hook(Some(test_unsupported_functions_hook));
+        xTaskNotifyGive(null_mut());
+        assert_eq!(TEST_UNSUPPORTED_FUNCTIONS_NAMES.lock().unwrap().len(), 4);
+        martos_freertos_set_unsupported_hook(None);
+    }
+}
@@ -0,0 +1,110 @@
+//! Testing harness for host tests. It initializes Martos, checks at the end of the test that
+//! the test did not leave timers acquired or tasks running, and shuts the system down,
+//! so the next test starts from a clean state.
+
+use martos::task_manager::{TaskIdType, TaskManager};
+use martos::timer::{list_timers, TimerSlotState};
+use martos::{init_system, shutdown_system};
+use std::fmt;
+
+/// Resources, that were left by the test.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LeakReport {
+    /// Indices of timers, that are still acquired by user.
+    pub acquired_timers: Vec<u8>,
+    /// Identifiers of tasks, that are not completed.
+    pub unfinished_tasks: Vec<TaskIdType>,
+}
+
+impl LeakReport {
+    /// Returns true if nothing is leaked.
+    pub fn is_clean(&self) -> bool {
+        self.acquired_timers.is_empty() && self.unfinished_tasks.is_empty()
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "leaked timers: {:?}, unfinished tasks: {:?}",
+            self.acquired_timers, self.unfinished_tasks
+        )
+    }
+}
+
+/// Testing harness. The checks are done by [TestHarness::finish] or when the harness is dropped.
+pub struct TestHarness {
+    /// Indicator whether acquired timers are reported.
+    check_timers: bool,
+    /// Indicator whether unfinished tasks are reported.
+    check_tasks: bool,
+    /// Indicator whether the checks are already done.
+    is_finished: bool,
+}
+
+impl TestHarness {
+    /// Initializes Martos for the test. State left by previous tests without harness is discarded.
+    pub fn new() -> Self {
+        shutdown_system().expect("Host port supports shutdown.");
+        init_system();
+        TestHarness {
+            check_timers: true,
+            check_tasks: true,
+            is_finished: false,
+        }
+    }
+
+    /// Disables check of acquired timers for the test, that leaves them intentionally.
+    #[allow(dead_code)]
+    pub fn allow_timer_leaks(mut self) -> Self {
+        self.check_timers = false;
+        self
+    }
+
+    /// Disables check of unfinished tasks for the test, that leaves them intentionally.
+    #[allow(dead_code)]
+    pub fn allow_task_leaks(mut self) -> Self {
+        self.check_tasks = false;
+        self
+    }
+
+    /// Collects leaked resources and shuts Martos down.
+    pub fn finish(mut self) -> LeakReport {
+        self.teardown()
+    }
+
+    /// Collects leaked resources according to enabled checks and shuts Martos down.
+    fn teardown(&mut self) -> LeakReport {
+        self.is_finished = true;
+        let mut report = LeakReport::default();
+        if self.check_timers {
+            report.acquired_timers = list_timers()
+                .iter()
+                .filter(|slot| slot.state == TimerSlotState::UserAcquired)
+                .map(|slot| slot.index)
+                .collect();
+        }
+        if self.check_tasks {
+            TaskManager::for_each_task(|task| {
+                if !task.is_finished() {
+                    report.unfinished_tasks.push(task.id());
+                }
+            });
+        }
+        shutdown_system().expect("Host port supports shutdown.");
+        report
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        if self.is_finished {
+            return;
+        }
+        let report = self.teardown();
+        if !report.is_clean() && !std::thread::panicking() {
+            panic!("test leaked resources: {}", report);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod port_tests {
+    use super::super::*;
+    use crate::ports::{HeapPort, PortTrait, TimerPort};
+    use crate::timer::{TimerError, TimerSlotState};
+
+    /// Checks that the platform implements all capabilities, that Martos requires.
+    fn is_port<P: PortTrait>() {}
+
+    #[test]
+    /// Tests that the minimal port implements required capabilities and its timer can be acquired once.
+    fn test_minimal_port() {
+        is_port::<Minimal>();
+        Minimal::init_heap();
+        Minimal::setup_hardware_timer();
+        assert_eq!(Minimal::timer_count(), 1);
+        assert!(!Minimal::valid_timer_index(1));
+        assert!(Minimal::try_acquire_timer(0));
+        assert!(!Minimal::try_acquire_timer(0));
+        Minimal::release_hardware_timer(0);
+        assert!(Minimal::try_acquire_timer(0));
+        Minimal::release_hardware_timer(0);
+    }
+
+    #[test]
+    /// Tests default implementations of optional timer functions.
+    fn test_default_timer_functions() {
+        assert!(!Minimal::reserve_timer(0, "test"));
+        assert_eq!(Minimal::timer_slot_state(0), TimerSlotState::Free);
+        assert!(!Minimal::stop_hardware_timer(0));
+        assert_eq!(Minimal::timer_source_clock_hz(), 1_000_000);
+        assert_eq!(Minimal::timer_divider(0), 1);
+        assert_eq!(Minimal::set_timer_divider(0, 1), Ok(()));
+        assert_eq!(
+            Minimal::set_timer_divider(0, 2),
+            Err(TimerError::UnsupportedResolution)
+        );
+        assert!(!Minimal::reset_hardware_timers());
+        assert_eq!(Minimal::cpu_freq_hz(), 0);
+        // CPU frequency is unknown, so delay returns immediately.
+        Minimal::delay_us(1_000);
+    }
+}
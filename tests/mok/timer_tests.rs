@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod timer_tests {
+    extern crate std;
+
+    use super::super::*;
+    use crate::ports::{Port, TimerPort};
+    use crate::timer::{
+        delay_ms, delay_us, list_timers, Timer, TimerError, TimerResolution, TimerSlotState,
+    };
+    use core::time::Duration;
+    use std::sync::Mutex;
+
+    /// Lock for tests, that advance the virtual clock and check exact intervals of timer 0.
+    static VIRTUAL_CLOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    /// Tests that the number of timer slots matches the number of timers of the port.
+    fn test_timer_count() {
+        assert_eq!(Port::timer_count(), TIMER_COUNT);
+        assert_eq!(list_timers().len(), TIMER_COUNT as usize);
+        for (index, slot) in list_timers().iter().enumerate() {
+            assert_eq!(slot.index as usize, index);
+        }
+    }
+
+    #[test]
+    /// Tests that reserved timer can not be acquired by user and counts time
+    /// independently of timers acquired by user.
+    fn test_reserved_timer() {
+        let _clock = VIRTUAL_CLOCK.lock().unwrap();
+        assert!(Port::reserve_timer(3, "test"));
+        assert_eq!(timer_slot_state(3), TimerSlotState::Reserved("test"));
+        assert_eq!(
+            Timer::get_timer(3).err(),
+            Some(TimerError::ReservedBySystem("test"))
+        );
+        // Timer can not be reserved twice.
+        assert!(!Port::reserve_timer(3, "other"));
+        assert_eq!(list_timers()[3].state, TimerSlotState::Reserved("test"));
+
+        Port::start_hardware_timer(3);
+        delay_us(100);
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        timer.start_timer();
+        delay_us(50);
+        assert_eq!(timer.get_time(), Duration::from_micros(50));
+        assert_eq!(Port::get_time(3), Duration::from_micros(150));
+        timer.release_timer();
+        assert_eq!(Port::get_time(3), Duration::from_micros(150));
+    }
+
+    #[test]
+    /// Tests that timer acquired by user can be neither acquired again nor reserved.
+    fn test_user_acquired_timer() {
+        let timer = Timer::get_timer(2).expect("The timer is already active.");
+        assert_eq!(timer_slot_state(2), TimerSlotState::UserAcquired);
+        assert_eq!(Timer::get_timer(2).err(), Some(TimerError::Busy));
+        assert!(!Port::reserve_timer(2, "test"));
+
+        timer.release_timer();
+        assert_eq!(timer_slot_state(2), TimerSlotState::Free);
+    }
+
+    #[test]
+    /// Tests that timer, that owns no hardware timer, releases nothing when dropped.
+    fn test_drop_unacquired_timer() {
+        let timer = Timer::unacquired();
+        assert!(!Port::valid_timer_index(timer.timer_index()));
+        drop(timer);
+    }
+
+    #[test]
+    /// Tests getting and reserving timer with invalid index.
+    fn test_invalid_timer_index() {
+        assert_eq!(
+            Timer::get_timer(TIMER_COUNT).err(),
+            Some(TimerError::InvalidIndex)
+        );
+        assert!(!Port::reserve_timer(TIMER_COUNT, "test"));
+    }
+
+    #[test]
+    /// Tests that free timer can be acquired by user after system initialization.
+    fn test_acquire_timer_after_init_system() {
+        crate::init_system();
+        let timer = Timer::get_timer(1).expect("The timer is already active.");
+        timer.release_timer();
+    }
+
+    #[test]
+    /// Tests that delays advance the virtual clock by exactly the requested amount
+    /// and that zero delay returns immediately.
+    fn test_delay_advances_virtual_clock() {
+        let _clock = VIRTUAL_CLOCK.lock().unwrap();
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        timer.start_timer();
+        let start = timer.get_time();
+
+        delay_us(0);
+        delay_ms(0);
+        assert_eq!(timer.get_time(), start);
+
+        delay_us(1_500);
+        assert_eq!(timer.get_time() - start, Duration::from_micros(1_500));
+        delay_ms(2);
+        assert_eq!(timer.get_time() - start, Duration::from_micros(3_500));
+
+        timer.release_timer();
+    }
+
+    #[test]
+    /// Tests that time of the timer is scaled by its resolution and rounded down to whole ticks.
+    fn test_timer_resolution() {
+        let _clock = VIRTUAL_CLOCK.lock().unwrap();
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        assert_eq!(timer.resolution(), Duration::from_micros(1));
+
+        timer.set_resolution(TimerResolution::Nanos100).unwrap();
+        assert_eq!(timer.resolution(), Duration::from_nanos(100));
+        timer.start_timer();
+        advance_virtual_time_ns(1_234_500);
+        assert_eq!(timer.get_time(), Duration::from_nanos(1_234_500));
+        advance_virtual_time_ns(99);
+        assert_eq!(timer.get_time(), Duration::from_nanos(1_234_500));
+        // Resolution of started timer can not be changed.
+        assert_eq!(
+            timer.set_resolution(TimerResolution::Micros1),
+            Err(TimerError::Running)
+        );
+        timer.release_timer();
+
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        // Released timer returns to default resolution.
+        assert_eq!(timer.resolution(), Duration::from_micros(1));
+        timer.start_timer();
+        advance_virtual_time_ns(1_234_500);
+        assert_eq!(timer.get_time(), Duration::from_micros(1_234));
+        timer.release_timer();
+
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        // Divider 2 of 80 MHz clock gives 25 ns ticks.
+        timer.set_resolution(TimerResolution::Divider(2)).unwrap();
+        assert_eq!(timer.resolution(), Duration::from_nanos(25));
+        timer.start_timer();
+        advance_virtual_time_ns(1_010);
+        assert_eq!(timer.get_time(), Duration::from_nanos(1_000));
+        timer.release_timer();
+
+        let timer = Timer::get_timer(0).expect("The timer is already active.");
+        assert_eq!(
+            timer.set_resolution(TimerResolution::Divider(0)),
+            Err(TimerError::UnsupportedResolution)
+        );
+        timer.release_timer();
+    }
+
+    #[test]
+    /// Tests that system timers are checked for valid and distinct indices.
+    fn test_valid_system_timers() {
+        assert!(crate::ports::valid_system_timers(
+            crate::ports::SYSTEM_TIMERS,
+            TIMER_COUNT
+        ));
+        assert!(crate::ports::valid_system_timers(
+            &[(0, "preemptive"), (1, "network")],
+            2
+        ));
+        assert!(!crate::ports::valid_system_timers(
+            &[(1, "preemptive"), (1, "network")],
+            2
+        ));
+        assert!(!crate::ports::valid_system_timers(&[(2, "network")], 2));
+        // System clock is declared among system timers.
+        assert_eq!(crate::ports::CLOCK_TIMER, Some(TIMER_COUNT - 1));
+        assert_eq!(
+            crate::ports::find_system_timer(&[(0, "preemptive"), (1, "clock")], "clock"),
+            Some(1)
+        );
+        assert_eq!(
+            crate::ports::find_system_timer(&[(0, "preemptive")], "clock"),
+            None
+        );
+    }
+}
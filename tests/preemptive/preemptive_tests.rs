@@ -0,0 +1,665 @@
+#[cfg(test)]
+mod preemption_tests {
+    use super::super::*;
+    extern crate std;
+
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use sequential_test::sequential;
+
+    /// Number of stall reports.
+    static TEST_STALL_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    /// Stall hook for testing.
+    fn test_on_stall() {
+        TEST_STALL_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Setup function for task for testing.
+    fn test_setup_fn() {}
+    /// Loop function for task for testing.
+    fn test_loop_fn() {}
+    /// Stop condition function for task for testing.
+    fn test_stop_condition_fn() -> bool {
+        false
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that stall of the timer interrupt is reported once and supervision is rearmed after ticks resume.
+    fn test_preemption_stall_supervisor() {
+        PreemptiveTaskManager::add_task(test_setup_fn, test_loop_fn, test_stop_condition_fn);
+        PreemptiveTaskManager::on_preemption_stall(2, test_on_stall);
+        let stalls = PreemptiveTaskManager::stall_count();
+
+        // Healthy interrupt: ticks advance between checks.
+        for _ in 0..3 {
+            let ticks = PreemptiveTaskManager::isr_tick_count();
+            PreemptiveTaskManager::schedule(&mut ());
+            assert_eq!(PreemptiveTaskManager::isr_tick_count(), ticks + 1);
+            PreemptiveTaskManager::check_preemption();
+            PreemptiveTaskManager::check_preemption();
+        }
+        assert_eq!(TEST_STALL_COUNTER.load(Ordering::Relaxed), 0);
+
+        // Stalled interrupt: reported only once.
+        for _ in 0..10 {
+            PreemptiveTaskManager::check_preemption();
+        }
+        assert_eq!(TEST_STALL_COUNTER.load(Ordering::Relaxed), 1);
+
+        // Interrupt resumes and stalls again: reported once more.
+        PreemptiveTaskManager::schedule(&mut ());
+        for _ in 0..10 {
+            PreemptiveTaskManager::check_preemption();
+        }
+        assert_eq!(TEST_STALL_COUNTER.load(Ordering::Relaxed), 2);
+        assert_eq!(PreemptiveTaskManager::stall_count(), stalls + 2);
+
+        // Counters are reported by diagnostics.
+        let mut dump = std::string::String::new();
+        crate::diagnostics::dump_state(&mut dump).unwrap();
+        let line = std::format!(
+            "isr_ticks={} stalls={}",
+            PreemptiveTaskManager::isr_tick_count(),
+            stalls + 2
+        );
+        assert!(dump.lines().any(|dump_line| dump_line == line));
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that time of the last context switch is recorded, when task manager has a clock.
+    fn test_last_switch_uptime() {
+        PreemptiveTaskManager::reset();
+        PreemptiveTaskManager::add_task(test_setup_fn, test_loop_fn, test_stop_condition_fn);
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(PreemptiveTaskManager::last_switch_uptime_us(), None);
+
+        // Timer is only read by the scheduler, so timer of other tests can be used as clock.
+        unsafe { (*addr_of_mut!(TASK_MANAGER)).clock_timer = Some(0) };
+        PreemptiveTaskManager::schedule(&mut ());
+        assert!(PreemptiveTaskManager::last_switch_uptime_us().is_some());
+        PreemptiveTaskManager::reset();
+        assert_eq!(PreemptiveTaskManager::last_switch_uptime_us(), None);
+    }
+
+    /// Returns identifier of the thread, that is selected for execution.
+    fn current_thread_id() -> TaskIdType {
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        task_manager.tasks[task_manager.task_to_execute_index].id
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that threads of higher priority are executed in round-robin order
+    /// and threads of lower priority are executed only after they are finished.
+    fn test_priority_scheduling() {
+        PreemptiveTaskManager::reset();
+        let low = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+        let high1 = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        let middle = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            3,
+        );
+        // Too high priority is truncated, so both high threads have the same priority.
+        let high2 = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY + 1,
+        );
+
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            PreemptiveTaskManager::schedule(&mut ());
+            order.push(current_thread_id());
+        }
+        assert_eq!(order, [high1, high2, high1, high2]);
+
+        PreemptiveTaskManager::finish_current_thread();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), high1);
+        PreemptiveTaskManager::finish_current_thread();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), middle);
+        PreemptiveTaskManager::finish_current_thread();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), low);
+        // The last thread is kept, when all threads are finished.
+        PreemptiveTaskManager::finish_current_thread();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), low);
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that thread of low priority is not starved by infinite thread of high priority
+    /// and gets a time slice within the documented bound.
+    fn test_priority_aging() {
+        PreemptiveTaskManager::reset();
+        let low = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        let bound = (MAX_TASK_PRIORITY as usize + 1) * AGING_SLICES as usize;
+
+        let mut order = Vec::new();
+        for _ in 0..10 * bound {
+            PreemptiveTaskManager::schedule(&mut ());
+            order.push(current_thread_id());
+        }
+        let low_slices: Vec<usize> = order
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| **id == low)
+            .map(|(slice, _)| slice)
+            .collect();
+        assert!(low_slices.len() >= 9);
+        assert!(low_slices[0] <= bound);
+        for pair in low_slices.windows(2) {
+            assert!(pair[1] - pair[0] <= bound + 1);
+        }
+        // High thread still gets almost all time slices.
+        assert!(order.iter().filter(|id| **id == high).count() > 9 * bound);
+        PreemptiveTaskManager::reset();
+    }
+
+    /// Number of jobs of periodic task with 10 ms period.
+    static TEST_RATE_MONOTONIC_FAST_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of jobs of periodic task with 50 ms period.
+    static TEST_RATE_MONOTONIC_MIDDLE_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of jobs of periodic task with 100 ms period.
+    static TEST_RATE_MONOTONIC_SLOW_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Loop function for periodic task with 10 ms period.
+    fn test_rate_monotonic_fast_loop_fn() {
+        TEST_RATE_MONOTONIC_FAST_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for periodic task with 50 ms period.
+    fn test_rate_monotonic_middle_loop_fn() {
+        TEST_RATE_MONOTONIC_MIDDLE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for periodic task with 100 ms period.
+    fn test_rate_monotonic_slow_loop_fn() {
+        TEST_RATE_MONOTONIC_SLOW_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for periodic task, that is longer than its period.
+    fn test_rate_monotonic_overlong_loop_fn() {
+        crate::timer::delay_ms(15);
+    }
+
+    /// Returns specification of periodic task with the specified period in milliseconds.
+    fn periodic_spec(period_ms: u64, loop_fn: TaskLoopFunctionType) -> PeriodicSpec {
+        PeriodicSpec {
+            period: Duration::from_millis(period_ms),
+            setup_fn: test_setup_fn,
+            loop_fn,
+            stop_condition_fn: test_stop_condition_fn,
+        }
+    }
+
+    /// Takes timer 0 as clock of task manager, so periods are measured by the virtual clock.
+    fn start_test_clock() {
+        assert!(Port::try_acquire_timer(0));
+        Port::start_hardware_timer(0);
+        unsafe { (*addr_of_mut!(TASK_MANAGER)).clock_timer = Some(0) };
+    }
+
+    /// Executes 1 ms time slices. In every slice the selected thread runs its job,
+    /// if it is released, like the thread does after context switch.
+    fn run_periodic_slices(count: u32) {
+        for _ in 0..count {
+            PreemptiveTaskManager::schedule(&mut ());
+            if Thread::try_start_current_job() {
+                let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+                (task_manager.tasks[task_manager.task_to_execute_index]
+                    .task
+                    .loop_fn)();
+                Thread::finish_current_job();
+            }
+            crate::timer::delay_ms(1);
+        }
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that priorities of periodic tasks are assigned rate-monotonically
+    /// and loop functions are called once per period.
+    fn test_rate_monotonic_set() {
+        PreemptiveTaskManager::reset();
+        start_test_clock();
+        let ids = PreemptiveTaskManager::add_rate_monotonic_set(
+            &[
+                periodic_spec(100, test_rate_monotonic_slow_loop_fn),
+                periodic_spec(10, test_rate_monotonic_fast_loop_fn),
+                periodic_spec(50, test_rate_monotonic_middle_loop_fn),
+            ],
+            2..=8,
+            false,
+        );
+        let priority = |id| PreemptiveTaskManager::get_task_info(id).unwrap().priority;
+        let (slow, fast, middle) = (ids[0], ids[1], ids[2]);
+        assert_eq!(
+            (priority(fast), priority(middle), priority(slow)),
+            (8, 7, 6)
+        );
+
+        run_periodic_slices(1000);
+        let jobs = [
+            TEST_RATE_MONOTONIC_FAST_COUNTER.load(Ordering::Relaxed),
+            TEST_RATE_MONOTONIC_MIDDLE_COUNTER.load(Ordering::Relaxed),
+            TEST_RATE_MONOTONIC_SLOW_COUNTER.load(Ordering::Relaxed),
+        ];
+        for (jobs, expected) in jobs.into_iter().zip([100, 20, 10]) {
+            assert!(jobs.abs_diff(expected) <= 1);
+        }
+        for id in ids {
+            assert_eq!(PreemptiveTaskManager::deadline_misses(id), Some(0));
+        }
+
+        // Narrow band: tasks with the longest periods share the lowest priority.
+        let ids = PreemptiveTaskManager::add_rate_monotonic_set(
+            &[
+                periodic_spec(10, test_loop_fn),
+                periodic_spec(50, test_loop_fn),
+                periodic_spec(100, test_loop_fn),
+            ],
+            MAX_TASK_PRIORITY..=MAX_TASK_PRIORITY + 1,
+            false,
+        );
+        assert!(ids.into_iter().all(|id| priority(id) == MAX_TASK_PRIORITY));
+        Port::release_hardware_timer(0);
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that jobs, which end after the next release, are counted as deadline misses
+    /// only for monitored tasks.
+    fn test_rate_monotonic_deadline_misses() {
+        PreemptiveTaskManager::reset();
+        start_test_clock();
+        let monitored = PreemptiveTaskManager::add_rate_monotonic_set(
+            &[
+                periodic_spec(10, test_rate_monotonic_overlong_loop_fn),
+                periodic_spec(1000, test_loop_fn),
+            ],
+            0..=MAX_TASK_PRIORITY,
+            true,
+        );
+        let unmonitored = PreemptiveTaskManager::add_rate_monotonic_set(
+            &[periodic_spec(10, test_rate_monotonic_overlong_loop_fn)],
+            0..=MAX_TASK_PRIORITY,
+            false,
+        );
+
+        run_periodic_slices(20);
+        let overlong_misses = PreemptiveTaskManager::deadline_misses(monitored[0]).unwrap();
+        assert!(overlong_misses >= 5);
+        assert_eq!(
+            PreemptiveTaskManager::deadline_misses(monitored[1]),
+            Some(0)
+        );
+        assert_eq!(
+            PreemptiveTaskManager::deadline_misses(unmonitored[0]),
+            Some(0)
+        );
+        assert_eq!(
+            PreemptiveTaskManager::deadline_misses(TaskIdType::MAX),
+            None
+        );
+        Port::release_hardware_timer(0);
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that sleeping thread is not selected until its sleeping time passes,
+    /// so threads of lower priority are executed meanwhile.
+    fn test_sleeping_thread_skipped() {
+        PreemptiveTaskManager::reset();
+        start_test_clock();
+        let low = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        assert_eq!(PreemptiveTaskManager::current_task_id(), None);
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(PreemptiveTaskManager::current_task_id(), Some(high));
+
+        assert!(PreemptiveTaskManager::release_current_after(
+            Duration::from_millis(5)
+        ));
+        for _ in 0..5 {
+            PreemptiveTaskManager::schedule(&mut ());
+            assert_eq!(current_thread_id(), low);
+            crate::timer::delay_ms(1);
+        }
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), high);
+        Port::release_hardware_timer(0);
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that timer interrupts do not switch away from thread with NO_PREEMPT flag
+    /// until the bound of kept time slices is reached, even to threads of higher priority.
+    fn test_no_preempt_flag() {
+        PreemptiveTaskManager::reset();
+        let flagged = PreemptiveTaskManager::add_task_with_flags(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            TaskFlags::NO_PREEMPT,
+            2,
+        );
+        let other = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+
+        let mut order = Vec::new();
+        for _ in 0..7 {
+            PreemptiveTaskManager::schedule(&mut ());
+            order.push(current_thread_id());
+        }
+        assert_eq!(
+            order,
+            [flagged, flagged, flagged, other, flagged, flagged, flagged]
+        );
+        assert_eq!(
+            PreemptiveTaskManager::get_task_info(flagged).map(|info| info.flags),
+            Some(TaskFlags::NO_PREEMPT)
+        );
+        PreemptiveTaskManager::reset();
+
+        // Thread of higher priority waits for the bound too.
+        let flagged = PreemptiveTaskManager::add_task_with_flags(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            TaskFlags::NO_PREEMPT,
+            1,
+        );
+        PreemptiveTaskManager::schedule(&mut ());
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        let ticks = PreemptiveTaskManager::isr_tick_count();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), flagged);
+        // Suppressed ticks are still counted by the interrupt counter.
+        assert_eq!(PreemptiveTaskManager::isr_tick_count(), ticks + 1);
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), high);
+        PreemptiveTaskManager::reset();
+
+        // Thread with EXCLUSIVE_BURST flag is switched to thread of higher priority immediately.
+        let burst = PreemptiveTaskManager::add_task_with_flags(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            TaskFlags::EXCLUSIVE_BURST,
+            2,
+        );
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), burst);
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), burst);
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), high);
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that diagnostics write identifier, status, priority and flags of every thread.
+    fn test_write_tasks() {
+        PreemptiveTaskManager::reset();
+        let flagged = PreemptiveTaskManager::add_task_with_flags(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            TaskFlags::NO_PREEMPT,
+            0,
+        );
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            MAX_TASK_PRIORITY,
+        );
+        PreemptiveTaskManager::schedule(&mut ());
+
+        let mut dump = std::string::String::new();
+        PreemptiveTaskManager::write_tasks(&mut dump).unwrap();
+        assert_eq!(
+            dump,
+            std::format!(
+                "count=2\ncurrent=1\n\
+                 task id={flagged} name=task-{flagged} status=ready priority=0 flags=2\n\
+                 task id={high} name=task-{high} status=running priority={MAX_TASK_PRIORITY} flags=0\n"
+            )
+        );
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that threads get aligned stacks of the requested sizes rounded up to the alignment.
+    fn test_thread_stack_size() {
+        PreemptiveTaskManager::reset();
+        PreemptiveTaskManager::add_task(test_setup_fn, test_loop_fn, test_stop_condition_fn);
+        PreemptiveTaskManager::add_task_with_stack_size(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            100,
+        );
+        PreemptiveTaskManager::add_task_with_stack_size(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            8192,
+        );
+
+        let task_manager = unsafe { &*addr_of!(TASK_MANAGER) };
+        let sizes: Vec<usize> = task_manager
+            .tasks
+            .iter()
+            .map(|thread| thread.stack_layout.size())
+            .collect();
+        assert_eq!(sizes, [THREAD_STACK_SIZE, 112, 8192]);
+        for thread in task_manager.tasks.iter() {
+            assert!(!thread.stack.is_null());
+            assert_eq!(thread.stack as usize % STACK_ALIGN, 0);
+            assert_eq!(thread.stack_layout.align(), STACK_ALIGN);
+        }
+        // Stacks are deallocated with their own layouts.
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that task snapshot reports the thread selected by the scheduler as running.
+    fn test_task_info() {
+        PreemptiveTaskManager::reset();
+        let low = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+        let high = PreemptiveTaskManager::add_priority_task(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+            2,
+        );
+        let info = |id, priority, status| TaskInfo {
+            id,
+            priority,
+            status,
+            restart_count: 0,
+            flags: TaskFlags::NONE,
+            name: [0; TASK_NAME_MAX_LEN],
+            inbox_dropped: 0,
+            wake_latency_iterations: 0,
+            boosted_wakes: 0,
+            wake_latency_us: 0,
+        };
+        assert_eq!(
+            PreemptiveTaskManager::list_tasks(),
+            [
+                info(low, 0, TaskStatus::Ready),
+                info(high, 2, TaskStatus::Ready)
+            ]
+        );
+
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(
+            PreemptiveTaskManager::get_task_info(high),
+            Some(info(high, 2, TaskStatus::Running))
+        );
+        PreemptiveTaskManager::finish_current_thread();
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(
+            PreemptiveTaskManager::list_tasks(),
+            [
+                info(low, 0, TaskStatus::Running),
+                info(high, 2, TaskStatus::Finished)
+            ]
+        );
+        assert_eq!(PreemptiveTaskManager::get_task_info(TaskIdType::MAX), None);
+        // Threads are unnamed.
+        assert_eq!(
+            PreemptiveTaskManager::get_task_info(low).unwrap().name(),
+            None
+        );
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that terminated threads are removed at context switches and the running one after it is switched.
+    fn test_terminate_task() {
+        PreemptiveTaskManager::reset();
+        let add = || {
+            PreemptiveTaskManager::add_task_with_id(
+                test_setup_fn,
+                test_loop_fn,
+                test_stop_condition_fn,
+            )
+        };
+        let first = add();
+        let second = add();
+        let third = add();
+        assert_eq!(
+            PreemptiveTaskManager::terminate_current_task(),
+            Err(TaskError::NotFound)
+        );
+        assert!(PreemptiveTaskManager::current_cancel_token().is_none());
+        let ids = || -> Vec<TaskIdType> {
+            PreemptiveTaskManager::list_tasks()
+                .iter()
+                .map(|task| task.id)
+                .collect()
+        };
+
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), first);
+        assert_eq!(PreemptiveTaskManager::terminate_task(second), Ok(()));
+        assert_eq!(
+            PreemptiveTaskManager::terminate_task(second),
+            Err(TaskError::InvalidState)
+        );
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), third);
+        assert_eq!(ids(), [first, third]);
+
+        let token = PreemptiveTaskManager::current_cancel_token().unwrap();
+        assert!(!token.is_cancelled());
+        assert_eq!(PreemptiveTaskManager::terminate_current_task(), Ok(()));
+        assert!(token.is_cancelled());
+        assert!(PreemptiveTaskManager::is_current_thread_finished());
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), first);
+        assert_eq!(ids(), [first, third]);
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), first);
+        assert_eq!(ids(), [first]);
+        assert_eq!(
+            PreemptiveTaskManager::terminate_task(third),
+            Err(TaskError::NotFound)
+        );
+        PreemptiveTaskManager::reset();
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that thread, which reaches its stop condition, is removed like a terminated one.
+    fn test_finished_thread_removed() {
+        PreemptiveTaskManager::reset();
+        let first = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+        let second = PreemptiveTaskManager::add_task_with_id(
+            test_setup_fn,
+            test_loop_fn,
+            test_stop_condition_fn,
+        );
+
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(current_thread_id(), first);
+        PreemptiveTaskManager::finish_current_thread();
+        // The interrupted thread is removed at the next context switch.
+        PreemptiveTaskManager::schedule(&mut ());
+        assert_eq!(PreemptiveTaskManager::list_tasks().len(), 2);
+        PreemptiveTaskManager::schedule(&mut ());
+        let ids: Vec<TaskIdType> = PreemptiveTaskManager::list_tasks()
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, [second]);
+        assert_eq!(PreemptiveTaskManager::get_task_info(first), None);
+        PreemptiveTaskManager::reset();
+    }
+}
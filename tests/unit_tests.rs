@@ -1,11 +1,28 @@
-#[cfg(all(test, not(feature = "mips64_timer_tests")))]
+#[cfg(all(test, not(feature = "mips64_timer_tests"), not(feature = "preemptive")))]
+mod harness;
+
+#[cfg(all(test, not(feature = "mips64_timer_tests"), not(feature = "preemptive")))]
 mod unit_tests {
+    use crate::harness::TestHarness;
+    use martos::diagnostics::dump_state;
+    use martos::error::{MartosError, SystemError};
     use martos::task_manager::TaskManager;
     use martos::task_manager::TaskManagerTrait;
-    use martos::timer::Timer;
+    use martos::task_manager::{
+        DeadlockReport, ExitReason, InboxError, InboxMessage, JoinHandleState, RestartPolicy,
+        TaskError, TaskFlags, TaskIdType, TaskInfo, TaskSpec, TaskStatus, WaitReason,
+        TASK_NAME_MAX_LEN,
+    };
+    use martos::timer::{
+        delay_ms, delay_ms_cancellable, list_timers, Timer, TimerError, TimerSlotState,
+    };
+    use martos::{init_system, set_version_logger, shutdown_system, version, VersionInfo};
     use sequential_test::sequential;
     use std::{
-        sync::atomic::{AtomicU32, Ordering},
+        sync::{
+            atomic::{AtomicBool, AtomicU32, Ordering},
+            Mutex,
+        },
         time::Duration,
     };
 
@@ -112,16 +129,17 @@ mod unit_tests {
     #[sequential]
     /// Tests if task manager with two finite tasks works correctly during some time without panic.
     fn test_two_finite_tasks_task_manager() {
-        TaskManager::add_task(
+        let id1 = TaskManager::add_task_with_id(
             test_two_finite_tasks_task_manager_setup_fn1,
             test_two_finite_tasks_task_manager_loop_fn1,
             test_two_finite_tasks_task_manager_stop_condition_fn1,
         );
-        TaskManager::add_task(
+        let id2 = TaskManager::add_task_with_id(
             test_two_finite_tasks_task_manager_setup_fn2,
             test_two_finite_tasks_task_manager_loop_fn2,
             test_two_finite_tasks_task_manager_stop_condition_fn2,
         );
+        assert_ne!(id1, id2);
         TaskManager::test_start_task_manager();
 
         assert_eq!(
@@ -262,9 +280,674 @@ mod unit_tests {
         );
     }
 
+    /// Counter of setup calls for task for test_max_times_restartable_task_task_manager.
+    static TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_SETUPS: AtomicU32 = AtomicU32::new(0);
+    /// Counter of loop calls for task for test_max_times_restartable_task_task_manager.
+    static TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for task for test_max_times_restartable_task_task_manager.
+    fn test_max_times_restartable_task_task_manager_setup_fn() {
+        TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_SETUPS.fetch_add(1, Ordering::Relaxed);
+        TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.store(0, Ordering::Relaxed);
+    }
+    /// Loop function for task for test_max_times_restartable_task_task_manager.
+    fn test_max_times_restartable_task_task_manager_loop_fn() {
+        TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for task for test_max_times_restartable_task_task_manager.
+    fn test_max_times_restartable_task_task_manager_stop_condition_fn() -> bool {
+        TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 5
+    }
+    #[test]
+    #[sequential]
+    /// Tests if task with restart policy MaxTimes(2) lives exactly three lifetimes.
+    fn test_max_times_restartable_task_task_manager() {
+        TaskManager::add_restartable_task(
+            test_max_times_restartable_task_task_manager_setup_fn,
+            test_max_times_restartable_task_task_manager_loop_fn,
+            test_max_times_restartable_task_task_manager_stop_condition_fn,
+            RestartPolicy::MaxTimes(2),
+        );
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_SETUPS.load(Ordering::Relaxed),
+            3
+        );
+        assert_eq!(
+            TEST_MAX_TIMES_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.load(Ordering::Relaxed),
+            5
+        );
+    }
+
+    /// Counter of setup calls for task for test_always_restartable_task_task_manager.
+    static TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_SETUPS: AtomicU32 = AtomicU32::new(0);
+    /// Counter of loop calls for task for test_always_restartable_task_task_manager.
+    static TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for task for test_always_restartable_task_task_manager.
+    fn test_always_restartable_task_task_manager_setup_fn() {
+        TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_SETUPS.fetch_add(1, Ordering::Relaxed);
+        TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.store(0, Ordering::Relaxed);
+    }
+    /// Loop function for task for test_always_restartable_task_task_manager.
+    fn test_always_restartable_task_task_manager_loop_fn() {
+        TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for task for test_always_restartable_task_task_manager.
+    fn test_always_restartable_task_task_manager_stop_condition_fn() -> bool {
+        TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 2
+    }
+    #[test]
+    #[sequential]
+    /// Tests if task with restart policy Always keeps restarting during some time.
+    fn test_always_restartable_task_task_manager() {
+        TaskManager::add_restartable_task(
+            test_always_restartable_task_task_manager_setup_fn,
+            test_always_restartable_task_task_manager_loop_fn,
+            test_always_restartable_task_task_manager_stop_condition_fn,
+            RestartPolicy::Always,
+        );
+        TaskManager::test_start_task_manager();
+        let setups = TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_SETUPS.load(Ordering::Relaxed);
+        assert!(setups > 3);
+
+        TaskManager::test_start_task_manager();
+        assert!(TEST_ALWAYS_RESTARTABLE_TASK_TASK_MANAGER_SETUPS.load(Ordering::Relaxed) > setups);
+    }
+
+    /// Counter of setup calls for task for test_restart_without_setup_task_manager.
+    static TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_SETUPS: AtomicU32 = AtomicU32::new(0);
+    /// Counter of loop calls for task for test_restart_without_setup_task_manager.
+    static TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Marker of reached stop condition for test_restart_without_setup_task_manager.
+    static TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_STOP: AtomicBool = AtomicBool::new(false);
+    /// Setup function for task for test_restart_without_setup_task_manager.
+    fn test_restart_without_setup_task_manager_setup_fn() {
+        TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_SETUPS.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for task for test_restart_without_setup_task_manager.
+    fn test_restart_without_setup_task_manager_loop_fn() {
+        let counter =
+            TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
+        if counter.is_multiple_of(3) {
+            TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_STOP.store(true, Ordering::Relaxed);
+        }
+    }
+    /// Stop function for task for test_restart_without_setup_task_manager.
+    fn test_restart_without_setup_task_manager_stop_condition_fn() -> bool {
+        TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_STOP.swap(false, Ordering::Relaxed)
+    }
+    #[test]
+    #[sequential]
+    /// Tests that restarted task continues with its loop function, when setup on restart is disabled.
+    fn test_restart_without_setup_task_manager() {
+        let harness = TestHarness::new();
+        let id = TaskManager::add_restartable_task(
+            test_restart_without_setup_task_manager_setup_fn,
+            test_restart_without_setup_task_manager_loop_fn,
+            test_restart_without_setup_task_manager_stop_condition_fn,
+            RestartPolicy::MaxTimes(2),
+        );
+        assert!(TaskManager::set_restart_setup(id, false));
+        assert!(!TaskManager::set_restart_setup(TaskIdType::MAX, false));
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_SETUPS.load(Ordering::Relaxed),
+            1
+        );
+        assert_eq!(
+            TEST_RESTART_WITHOUT_SETUP_TASK_MANAGER_COUNTER.load(Ordering::Relaxed),
+            9
+        );
+        assert_eq!(TaskManager::get_task_info(id).unwrap().restart_count, 2);
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Counter of setup calls for restarted task for test_delete_always_restartable_task_manager.
+    static TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_SETUPS: AtomicU32 = AtomicU32::new(0);
+    /// Counter of loop calls for deleting task for test_delete_always_restartable_task_manager.
+    static TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Identifier of restarted task for test_delete_always_restartable_task_manager.
+    static TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_VICTIM: Mutex<TaskIdType> = Mutex::new(0);
+    /// Setup function for restarted task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_setup_fn() {
+        TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_SETUPS.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for restarted task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_loop_fn() {}
+    /// Stop function for restarted task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_stop_condition_fn() -> bool {
+        true
+    }
+    /// Setup function for deleting task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_killer_setup_fn() {}
+    /// Loop function for deleting task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_killer_loop_fn() {
+        if TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed) == 2
+        {
+            TaskManager::delete_task(
+                *TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_VICTIM
+                    .lock()
+                    .unwrap(),
+            );
+        }
+    }
+    /// Stop function for deleting task for test_delete_always_restartable_task_manager.
+    fn test_delete_always_restartable_task_manager_killer_stop_condition_fn() -> bool {
+        TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 3
+    }
+    #[test]
+    #[sequential]
+    /// Tests that explicit deletion of the task wins over restart policy Always.
+    fn test_delete_always_restartable_task_manager() {
+        let harness = TestHarness::new();
+        let victim = TaskManager::add_restartable_task(
+            test_delete_always_restartable_task_manager_setup_fn,
+            test_delete_always_restartable_task_manager_loop_fn,
+            test_delete_always_restartable_task_manager_stop_condition_fn,
+            RestartPolicy::Always,
+        );
+        *TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_VICTIM
+            .lock()
+            .unwrap() = victim;
+        TaskManager::add_task_with_id(
+            test_delete_always_restartable_task_manager_killer_setup_fn,
+            test_delete_always_restartable_task_manager_killer_loop_fn,
+            test_delete_always_restartable_task_manager_killer_stop_condition_fn,
+        );
+        TaskManager::test_start_task_manager();
+
+        let setups = TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_SETUPS.load(Ordering::Relaxed);
+        assert!(setups > 0);
+        assert!(TaskManager::get_task_info(victim).is_none());
+        assert_eq!(
+            TaskManager::try_delete_task(victim),
+            Err(TaskError::NotFound)
+        );
+        TaskManager::test_start_task_manager();
+        assert_eq!(
+            TEST_DELETE_ALWAYS_RESTARTABLE_TASK_MANAGER_SETUPS.load(Ordering::Relaxed),
+            setups
+        );
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Exit hook calls for test_exit_hook_task_manager.
+    static TEST_EXIT_HOOK_TASK_MANAGER_EXITS: Mutex<Vec<(TaskIdType, ExitReason)>> =
+        Mutex::new(Vec::new());
+    /// Counter of loop calls for task for test_exit_hook_task_manager.
+    static TEST_EXIT_HOOK_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for task for test_exit_hook_task_manager.
+    fn test_exit_hook_task_manager_setup_fn() {
+        TEST_EXIT_HOOK_TASK_MANAGER_COUNTER.store(0, Ordering::Relaxed);
+    }
+    /// Loop function for task for test_exit_hook_task_manager.
+    fn test_exit_hook_task_manager_loop_fn() {
+        TEST_EXIT_HOOK_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for task for test_exit_hook_task_manager.
+    fn test_exit_hook_task_manager_stop_condition_fn() -> bool {
+        TEST_EXIT_HOOK_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 3
+    }
+    /// Exit hook for task for test_exit_hook_task_manager.
+    fn test_exit_hook_task_manager_exit_hook(id: TaskIdType, reason: ExitReason) {
+        TEST_EXIT_HOOK_TASK_MANAGER_EXITS
+            .lock()
+            .unwrap()
+            .push((id, reason));
+    }
+    #[test]
+    #[sequential]
+    /// Tests if exit hook is called once per restart and once when the task completes.
+    fn test_exit_hook_task_manager() {
+        let id = TaskManager::add_restartable_task(
+            test_exit_hook_task_manager_setup_fn,
+            test_exit_hook_task_manager_loop_fn,
+            test_exit_hook_task_manager_stop_condition_fn,
+            RestartPolicy::MaxTimes(2),
+        );
+        assert!(TaskManager::set_exit_hook(
+            id,
+            test_exit_hook_task_manager_exit_hook
+        ));
+        assert!(!TaskManager::set_exit_hook(
+            TaskIdType::MAX,
+            test_exit_hook_task_manager_exit_hook
+        ));
+        TaskManager::test_start_task_manager();
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            *TEST_EXIT_HOOK_TASK_MANAGER_EXITS.lock().unwrap(),
+            vec![
+                (id, ExitReason::Restarted(1)),
+                (id, ExitReason::Restarted(2)),
+                (id, ExitReason::StopCondition),
+            ]
+        );
+        assert_eq!(ExitReason::StopCondition.code(), 0);
+        assert_eq!(ExitReason::Restarted(2).code(), 2);
+    }
+
+    /// Order of setup function calls for test_add_tasks_task_manager.
+    static TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+    /// First setup function for task for test_add_tasks_task_manager.
+    fn test_add_tasks_task_manager_setup_fn1() {
+        TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER
+            .lock()
+            .unwrap()
+            .push(1);
+    }
+    /// Second setup function for task for test_add_tasks_task_manager.
+    fn test_add_tasks_task_manager_setup_fn2() {
+        TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER
+            .lock()
+            .unwrap()
+            .push(2);
+    }
+    /// Third setup function for task for test_add_tasks_task_manager.
+    fn test_add_tasks_task_manager_setup_fn3() {
+        TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER
+            .lock()
+            .unwrap()
+            .push(3);
+    }
+    /// Loop function for task for test_add_tasks_task_manager.
+    fn test_add_tasks_task_manager_loop_fn() {}
+    /// Stop function for task for test_add_tasks_task_manager.
+    fn test_add_tasks_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests if batch of tasks gets consecutive ids and setup functions are called once in batch order.
+    fn test_add_tasks_task_manager() {
+        let ids = TaskManager::add_tasks(&[
+            TaskSpec {
+                setup_fn: test_add_tasks_task_manager_setup_fn3,
+                loop_fn: test_add_tasks_task_manager_loop_fn,
+                stop_condition_fn: test_add_tasks_task_manager_stop_condition_fn,
+            },
+            TaskSpec {
+                setup_fn: test_add_tasks_task_manager_setup_fn1,
+                loop_fn: test_add_tasks_task_manager_loop_fn,
+                stop_condition_fn: test_add_tasks_task_manager_stop_condition_fn,
+            },
+            TaskSpec {
+                setup_fn: test_add_tasks_task_manager_setup_fn2,
+                loop_fn: test_add_tasks_task_manager_loop_fn,
+                stop_condition_fn: test_add_tasks_task_manager_stop_condition_fn,
+            },
+        ]);
+
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[1], ids[0] + 1);
+        assert_eq!(ids[2], ids[0] + 2);
+        assert_eq!(
+            *TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER.lock().unwrap(),
+            vec![3, 1, 2]
+        );
+
+        TaskManager::test_start_task_manager();
+        assert_eq!(
+            *TEST_ADD_TASKS_TASK_MANAGER_SETUP_ORDER.lock().unwrap(),
+            vec![3, 1, 2]
+        );
+    }
+
+    /// Setup function for tasks for test_named_tasks_task_manager.
+    fn test_named_tasks_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_named_tasks_task_manager.
+    fn test_named_tasks_task_manager_loop_fn() {}
+    /// Stop function for tasks for test_named_tasks_task_manager.
+    fn test_named_tasks_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests if tasks can be found by name and first task is returned for duplicated names.
+    fn test_named_tasks_task_manager() {
+        let first_id = TaskManager::add_named_task(
+            "test-named-task",
+            test_named_tasks_task_manager_setup_fn,
+            test_named_tasks_task_manager_loop_fn,
+            test_named_tasks_task_manager_stop_condition_fn,
+        );
+        TaskManager::add_named_task(
+            "test-named-task",
+            test_named_tasks_task_manager_setup_fn,
+            test_named_tasks_task_manager_loop_fn,
+            test_named_tasks_task_manager_stop_condition_fn,
+        );
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            TaskManager::find_task_by_name("test-named-task"),
+            Some(first_id)
+        );
+        assert_eq!(TaskManager::find_task_by_name("test-missing-task"), None);
+
+        let mut dump = String::new();
+        dump_state(&mut dump).unwrap();
+        assert!(dump.contains(&format!("task id={} name=test-named-task ", first_id)));
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests if all tasks are visited in order of adding and visitor sees the same state as the dump.
+    fn test_for_each_task_task_manager() {
+        let id = TaskManager::add_named_task(
+            "test-visited-task",
+            test_named_tasks_task_manager_setup_fn,
+            test_named_tasks_task_manager_loop_fn,
+            test_named_tasks_task_manager_stop_condition_fn,
+        );
+        TaskManager::test_start_task_manager();
+
+        let mut ids = Vec::new();
+        let mut visited = None;
+        TaskManager::for_each_task(|task| {
+            ids.push(task.id());
+            if task.id() == id {
+                visited = Some((
+                    task.name().map(str::to_string),
+                    task.is_setup_completed(),
+                    task.is_finished(),
+                    task.inbox_stats(),
+                ));
+            }
+        });
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            visited,
+            Some((Some("test-visited-task".to_string()), true, false, None))
+        );
+
+        let mut dump = String::new();
+        dump_state(&mut dump).unwrap();
+        assert!(dump.contains(&format!("count={}\n", ids.len())));
+        assert!(dump.contains(&format!(
+            "task id={} name=test-visited-task setup_completed=true restarts=0\n",
+            id
+        )));
+    }
+
+    /// Identifiers of tasks for test_iteration_hooks_task_manager.
+    static TEST_ITERATION_HOOKS_TASK_MANAGER_IDS: Mutex<Vec<TaskIdType>> = Mutex::new(Vec::new());
+    /// Trace of hook calls for test_iteration_hooks_task_manager: identifier, phase and loop indicator.
+    static TEST_ITERATION_HOOKS_TASK_MANAGER_TRACE: Mutex<Vec<(TaskIdType, &str, bool)>> =
+        Mutex::new(Vec::new());
+    /// Counter for task for test_iteration_hooks_task_manager.
+    static TEST_ITERATION_HOOKS_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for task for test_iteration_hooks_task_manager.
+    fn test_iteration_hooks_task_manager_setup_fn() {}
+    /// Loop function for task for test_iteration_hooks_task_manager.
+    fn test_iteration_hooks_task_manager_loop_fn() {
+        TEST_ITERATION_HOOKS_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for task for test_iteration_hooks_task_manager.
+    fn test_iteration_hooks_task_manager_stop_condition_fn() -> bool {
+        TEST_ITERATION_HOOKS_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 2
+    }
+    /// Pre-iteration hook for test_iteration_hooks_task_manager.
+    fn test_iteration_hooks_task_manager_pre_hook(id: TaskIdType) {
+        if TEST_ITERATION_HOOKS_TASK_MANAGER_IDS
+            .lock()
+            .unwrap()
+            .contains(&id)
+        {
+            TEST_ITERATION_HOOKS_TASK_MANAGER_TRACE
+                .lock()
+                .unwrap()
+                .push((id, "pre", false));
+        }
+    }
+    /// Post-iteration hook for test_iteration_hooks_task_manager.
+    fn test_iteration_hooks_task_manager_post_hook(id: TaskIdType, ran_loop: bool) {
+        if TEST_ITERATION_HOOKS_TASK_MANAGER_IDS
+            .lock()
+            .unwrap()
+            .contains(&id)
+        {
+            TEST_ITERATION_HOOKS_TASK_MANAGER_TRACE
+                .lock()
+                .unwrap()
+                .push((id, "post", ran_loop));
+        }
+    }
+    #[test]
+    #[sequential]
+    /// Tests if iteration hooks are called around every iteration and report whether loop function ran.
+    fn test_iteration_hooks_task_manager() {
+        let id = TaskManager::add_restartable_task(
+            test_iteration_hooks_task_manager_setup_fn,
+            test_iteration_hooks_task_manager_loop_fn,
+            test_iteration_hooks_task_manager_stop_condition_fn,
+            RestartPolicy::Never,
+        );
+        TEST_ITERATION_HOOKS_TASK_MANAGER_IDS
+            .lock()
+            .unwrap()
+            .push(id);
+        TaskManager::set_iteration_hooks(
+            Some(test_iteration_hooks_task_manager_pre_hook),
+            Some(test_iteration_hooks_task_manager_post_hook),
+        );
+        TaskManager::test_start_task_manager();
+        TaskManager::set_iteration_hooks(None, None);
+
+        let trace = TEST_ITERATION_HOOKS_TASK_MANAGER_TRACE.lock().unwrap();
+        // Setup, two loop iterations and then turns of the finished task.
+        assert_eq!(
+            trace[..8],
+            [
+                (id, "pre", false),
+                (id, "post", false),
+                (id, "pre", false),
+                (id, "post", true),
+                (id, "pre", false),
+                (id, "post", true),
+                (id, "pre", false),
+                (id, "post", false),
+            ]
+        );
+        assert_eq!(trace.len() % 2, 0);
+        assert!(trace[8..]
+            .chunks(2)
+            .all(|turn| turn == [(id, "pre", false), (id, "post", false)]));
+        let turns = trace.len();
+        drop(trace);
+
+        TaskManager::test_start_task_manager();
+        assert_eq!(
+            TEST_ITERATION_HOOKS_TASK_MANAGER_TRACE
+                .lock()
+                .unwrap()
+                .len(),
+            turns
+        );
+    }
+
+    /// Trace of loop calls for test_exclusive_burst_task_manager.
+    static TEST_EXCLUSIVE_BURST_TASK_MANAGER_TRACE: Mutex<Vec<char>> = Mutex::new(Vec::new());
+    /// Setup function for tasks for test_exclusive_burst_task_manager.
+    fn test_exclusive_burst_task_manager_setup_fn() {}
+    /// Loop function for burst task for test_exclusive_burst_task_manager.
+    fn test_exclusive_burst_task_manager_loop_fn1() {
+        TEST_EXCLUSIVE_BURST_TASK_MANAGER_TRACE
+            .lock()
+            .unwrap()
+            .push('A');
+    }
+    /// Loop function for ordinary task for test_exclusive_burst_task_manager.
+    fn test_exclusive_burst_task_manager_loop_fn2() {
+        TEST_EXCLUSIVE_BURST_TASK_MANAGER_TRACE
+            .lock()
+            .unwrap()
+            .push('B');
+    }
+    /// Stop function for tasks for test_exclusive_burst_task_manager.
+    fn test_exclusive_burst_task_manager_stop_condition_fn() -> bool {
+        TEST_EXCLUSIVE_BURST_TASK_MANAGER_TRACE
+            .lock()
+            .unwrap()
+            .len()
+            >= 100
+    }
+    #[test]
+    #[sequential]
+    /// Tests if task with EXCLUSIVE_BURST flag runs three consecutive loop iterations before switching.
+    fn test_exclusive_burst_task_manager() {
+        TaskManager::add_task_with_flags(
+            test_exclusive_burst_task_manager_setup_fn,
+            test_exclusive_burst_task_manager_loop_fn1,
+            test_exclusive_burst_task_manager_stop_condition_fn,
+            TaskFlags::EXCLUSIVE_BURST,
+            3,
+        );
+        TaskManager::add_task(
+            test_exclusive_burst_task_manager_setup_fn,
+            test_exclusive_burst_task_manager_loop_fn2,
+            test_exclusive_burst_task_manager_stop_condition_fn,
+        );
+        TaskManager::test_start_task_manager();
+
+        let trace: String = TEST_EXCLUSIVE_BURST_TASK_MANAGER_TRACE
+            .lock()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(trace.len(), 100);
+        // Bursts between two ordinary iterations are exactly three iterations long.
+        let bursts: Vec<&str> = trace.split('B').collect();
+        assert!(bursts.len() > 2);
+        for burst in &bursts[1..bursts.len() - 1] {
+            assert_eq!(*burst, "AAA");
+        }
+        assert!(TaskFlags::EXCLUSIVE_BURST.contains(TaskFlags::EXCLUSIVE_BURST));
+        assert!(!TaskFlags::NONE.contains(TaskFlags::EXCLUSIVE_BURST));
+    }
+
+    /// Tags of received messages for test_inbox_task_manager.
+    static TEST_INBOX_TASK_MANAGER_RECEIVED: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+    /// Setup function for task for test_inbox_task_manager.
+    fn test_inbox_task_manager_setup_fn() {}
+    /// Loop function for task for test_inbox_task_manager.
+    fn test_inbox_task_manager_loop_fn() {
+        while let Some(message) = TaskManager::recv() {
+            assert_eq!(message.payload, [message.tag as u8; 8]);
+            TEST_INBOX_TASK_MANAGER_RECEIVED
+                .lock()
+                .unwrap()
+                .push(message.tag);
+        }
+    }
+    /// Stop function for task for test_inbox_task_manager.
+    fn test_inbox_task_manager_stop_condition_fn() -> bool {
+        TEST_INBOX_TASK_MANAGER_RECEIVED.lock().unwrap().len() >= 3
+    }
+    #[test]
+    #[sequential]
+    /// Tests if messages are received in order of sending and errors of sending are reported.
+    fn test_inbox_task_manager() {
+        let id = TaskManager::add_task_with_inbox(
+            test_inbox_task_manager_setup_fn,
+            test_inbox_task_manager_loop_fn,
+            test_inbox_task_manager_stop_condition_fn,
+            3,
+        );
+        for tag in 1..=3 {
+            let message = InboxMessage {
+                tag,
+                payload: [tag as u8; 8],
+            };
+            assert_eq!(TaskManager::send_to(id, message), Ok(()));
+        }
+        assert_eq!(
+            TaskManager::send_to(id, InboxMessage::default()),
+            Err(InboxError::Full)
+        );
+        assert_eq!(TaskManager::recv(), None);
+        assert_eq!(
+            TaskManager::send_to(TaskIdType::MAX, InboxMessage::default()),
+            Err(InboxError::NoTask)
+        );
+
+        let mut dump = String::new();
+        dump_state(&mut dump).unwrap();
+        assert!(dump.contains(&format!("task id={} name=task-{} ", id, id)));
+        assert!(dump.contains("inbox=3/3 inbox_dropped=1"));
+
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(*TEST_INBOX_TASK_MANAGER_RECEIVED.lock().unwrap(), [1, 2, 3]);
+        assert_eq!(
+            TaskManager::send_to(id, InboxMessage::default()),
+            Err(InboxError::TaskFinished)
+        );
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests if inbox errors have stable codes and are converted to MartosError.
+    fn test_inbox_error_code() {
+        assert_eq!(InboxError::NoTask.error_code(), -120);
+        assert_eq!(InboxError::NoInbox.error_code(), -121);
+        assert_eq!(InboxError::Full.error_code(), -122);
+        assert_eq!(InboxError::TaskFinished.error_code(), -123);
+        let error = MartosError::from(InboxError::Full);
+        assert_eq!(error.error_code(), -122);
+        assert_eq!(error.to_string(), "inbox error: task inbox is full");
+    }
+
+    /// Setup function for task for test_inbox_wake_on_send_task_manager.
+    fn test_inbox_wake_on_send_task_manager_setup_fn() {}
+    /// Loop function for task for test_inbox_wake_on_send_task_manager.
+    fn test_inbox_wake_on_send_task_manager_loop_fn() {}
+    /// Stop function for task for test_inbox_wake_on_send_task_manager.
+    fn test_inbox_wake_on_send_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that sending with wake flag wakes up sleeping task only when the message is delivered.
+    fn test_inbox_wake_on_send_task_manager() {
+        let harness = TestHarness::new();
+        let id = TaskManager::add_task_with_inbox(
+            test_inbox_wake_on_send_task_manager_setup_fn,
+            test_inbox_wake_on_send_task_manager_loop_fn,
+            test_inbox_wake_on_send_task_manager_stop_condition_fn,
+            2,
+        );
+        let status = || TaskManager::get_task_info(id).unwrap().status;
+        TaskManager::put_to_sleep(id);
+
+        // Sending without wake flag leaves the task sleeping.
+        assert_eq!(TaskManager::send_to(id, InboxMessage::default()), Ok(()));
+        assert_eq!(status(), TaskStatus::Sleeping);
+        assert_eq!(
+            TaskManager::send_to_and_wake(id, InboxMessage::default()),
+            Ok(())
+        );
+        assert_eq!(status(), TaskStatus::Ready);
+
+        // Dropped message does not wake the task up.
+        TaskManager::put_to_sleep(id);
+        assert_eq!(
+            TaskManager::send_to_and_wake(id, InboxMessage::default()),
+            Err(InboxError::Full)
+        );
+        assert_eq!(status(), TaskStatus::Sleeping);
+
+        TaskManager::delete_task(id);
+        assert!(harness.finish().is_clean());
+    }
+
     #[test]
+    #[sequential]
     /// Tests setup timer function and getting counter value (bad unit test).
     fn test_setup_timer() {
+        let _harness = TestHarness::new();
         Timer::setup_timer();
         let timer = Timer::get_timer(0)
             .expect("The timer is already active or a timer with this index does not exist.");
@@ -273,8 +956,10 @@ mod unit_tests {
     }
 
     #[test]
+    #[sequential]
     /// Tests loop timer function.
     fn test_loop_timer() {
+        let _harness = TestHarness::new();
         Timer::setup_timer();
         let mut timer = Timer::get_timer(0)
             .expect("The timer is already active or a timer with this index does not exist.");
@@ -284,8 +969,45 @@ mod unit_tests {
     }
 
     #[test]
+    #[sequential]
+    /// Tests that dropped timer releases the hardware timer, so it can be acquired again.
+    fn test_drop_timer() {
+        let _harness = TestHarness::new();
+        {
+            let timer = Timer::get_timer(0)
+                .expect("The timer is already active or a timer with this index does not exist.");
+            timer.start_timer();
+            assert_eq!(Timer::get_timer(0).err(), Some(TimerError::Busy));
+        }
+        let timer = Timer::get_timer(0).expect("The timer is not released by drop.");
+        drop(timer);
+        assert!(Timer::get_timer(0).is_ok());
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that dropping a timer releases only its own hardware timer.
+    fn test_drop_timer_releases_only_own_timer() {
+        let harness = TestHarness::new();
+        let first = Timer::get_timer(0).expect("The timer is already active.");
+        let second = Timer::get_timer(1).expect("The timer is already active.");
+        assert_eq!((first.timer_index(), second.timer_index()), (0, 1));
+        // Failed acquisition creates no timer, so nothing is released.
+        drop(Timer::get_timer(0));
+        assert_eq!(Timer::get_timer(0).err(), Some(TimerError::Busy));
+        drop(second);
+        assert_eq!(Timer::get_timer(0).err(), Some(TimerError::Busy));
+        assert_eq!(list_timers()[0].state, TimerSlotState::UserAcquired);
+        assert_eq!(list_timers()[1].state, TimerSlotState::Free);
+        first.release_timer();
+        assert!(harness.finish().is_clean());
+    }
+
+    #[test]
+    #[sequential]
     /// Tests stop condition timer function.
     fn test_stop_condition_timer() {
+        let _harness = TestHarness::new();
         let timer = Timer::get_timer(0)
             .expect("The timer is already active or a timer with this index does not exist.");
         timer.change_period_timer(Duration::new(10, 0));
@@ -293,4 +1015,1397 @@ mod unit_tests {
         assert!(!timer.stop_condition_timer());
         timer.release_timer();
     }
+
+    #[test]
+    /// Tests that every timer error has non-empty description and stable error code.
+    fn test_timer_error_display_and_code() {
+        let errors = [
+            TimerError::InvalidIndex,
+            TimerError::Busy,
+            TimerError::ReservedBySystem("network"),
+            TimerError::Running,
+            TimerError::UnsupportedResolution,
+        ];
+        for error in errors {
+            assert!(!error.to_string().is_empty());
+            assert!((-119..=-100).contains(&error.error_code()));
+        }
+        assert_eq!(TimerError::InvalidIndex.error_code(), -100);
+        assert_eq!(TimerError::Busy.error_code(), -101);
+        assert_eq!(TimerError::ReservedBySystem("network").error_code(), -102);
+        assert_eq!(TimerError::Running.error_code(), -103);
+        assert_eq!(TimerError::UnsupportedResolution.error_code(), -104);
+    }
+
+    #[test]
+    /// Tests conversion of subsystem errors into MartosError.
+    fn test_martos_error_from_timer_error() {
+        let error: MartosError = TimerError::Busy.into();
+        assert_eq!(error, MartosError::Timer(TimerError::Busy));
+        assert_eq!(error.error_code(), TimerError::Busy.error_code());
+        assert!(!error.to_string().is_empty());
+        assert!(std::error::Error::source(&error).is_some());
+
+        fn get_timer_with_question_mark() -> Result<Timer, MartosError> {
+            Ok(Timer::get_timer(u8::MAX)?)
+        }
+        assert_eq!(
+            get_timer_with_question_mark()
+                .err()
+                .map(|error| error.error_code()),
+            Some(TimerError::InvalidIndex.error_code())
+        );
+    }
+
+    /// Setup function for task for testing dump of the system state.
+    fn test_dump_state_setup_fn() {}
+    /// Loop function for task for testing dump of the system state.
+    fn test_dump_state_loop_fn() {}
+    /// Stop condition function for task for testing dump of the system state.
+    fn test_dump_state_stop_condition_fn() -> bool {
+        false
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that dump of the system state contains all sections and fields.
+    fn test_dump_state() {
+        let _harness = TestHarness::new().allow_task_leaks();
+        TaskManager::add_task(
+            test_dump_state_setup_fn,
+            test_dump_state_loop_fn,
+            test_dump_state_stop_condition_fn,
+        );
+        let timer = Timer::get_timer(0)
+            .expect("The timer is already active or a timer with this index does not exist.");
+
+        let mut dump = String::new();
+        dump_state(&mut dump).unwrap();
+        timer.release_timer();
+
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.first(), Some(&"=== martos state ==="));
+        assert_eq!(lines.last(), Some(&"=== end ==="));
+        assert!(lines.contains(&"[timers]"));
+        assert!(lines.contains(&"[tasks]"));
+        assert!(lines.contains(&"timer index=0 state=user"));
+        assert!(lines.iter().any(|line| line.starts_with("count=")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("task id=") && line.contains("restarts=0")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("task id=") && line.contains(" name=task-")));
+    }
+
+    /// Writer, that fails on every write.
+    struct FailingWriter;
+
+    impl std::fmt::Write for FailingWriter {
+        fn write_str(&mut self, _s: &str) -> std::fmt::Result {
+            Err(std::fmt::Error)
+        }
+    }
+
+    #[test]
+    #[sequential]
+    /// Tests that dump of the system state reports writer errors instead of panicking.
+    fn test_dump_state_failing_writer() {
+        assert!(dump_state(&mut FailingWriter).is_err());
+    }
+
+    /// Build information logged by init_system for test_version_logger.
+    static TEST_VERSION_LOGGER_LOGS: Mutex<Vec<VersionInfo>> = Mutex::new(Vec::new());
+    /// Version logger for test_version_logger.
+    fn test_version_logger_fn(info: &VersionInfo) {
+        TEST_VERSION_LOGGER_LOGS.lock().unwrap().push(*info);
+    }
+    #[test]
+    #[sequential]
+    /// Tests that init_system logs build information once per initialization, only when logger is set.
+    fn test_version_logger() {
+        let harness = TestHarness::new();
+        set_version_logger(Some(test_version_logger_fn));
+        init_system();
+        assert!(TEST_VERSION_LOGGER_LOGS.lock().unwrap().is_empty());
+
+        assert_eq!(shutdown_system(), Ok(()));
+        assert!(init_system());
+        assert!(!init_system());
+        assert_eq!(*TEST_VERSION_LOGGER_LOGS.lock().unwrap(), [version()]);
+
+        set_version_logger(None);
+        assert_eq!(shutdown_system(), Ok(()));
+        init_system();
+        assert_eq!(TEST_VERSION_LOGGER_LOGS.lock().unwrap().len(), 1);
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Setup function for task for test_shutdown_system.
+    fn test_shutdown_system_setup_fn() {}
+    /// Loop function for task for test_shutdown_system.
+    fn test_shutdown_system_loop_fn() {}
+    /// Stop function for task for test_shutdown_system.
+    fn test_shutdown_system_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that repeated initialization is harmless and reported and that shutdown releases timers
+    /// and removes tasks, so the system can be initialized and used again.
+    fn test_shutdown_system() {
+        init_system();
+        assert!(!init_system());
+        for _ in 0..3 {
+            TaskManager::add_task(
+                test_shutdown_system_setup_fn,
+                test_shutdown_system_loop_fn,
+                test_shutdown_system_stop_condition_fn,
+            );
+            let timer = Timer::get_timer(0)
+                .expect("The timer is already active or a timer with this index does not exist.");
+            timer.start_timer();
+            // The timer is left acquired, so shutdown has to release it.
+            std::mem::forget(timer);
+
+            assert_eq!(shutdown_system(), Ok(()));
+            let mut dump = String::new();
+            dump_state(&mut dump).unwrap();
+            let lines: Vec<&str> = dump.lines().collect();
+            assert!(lines.contains(&"count=0"));
+            assert!(lines.contains(&"timer index=0 state=free"));
+
+            assert!(init_system());
+            let timer = Timer::get_timer(0).expect("The timer is not released by shutdown.");
+            assert_eq!(timer.get_time(), Duration::ZERO);
+            timer.release_timer();
+        }
+        assert_eq!(
+            MartosError::from(SystemError::ReinitUnsupported).error_code(),
+            -1
+        );
+    }
+
+    /// Setup function for task for test_harness_reports_leaks.
+    fn test_harness_reports_leaks_setup_fn() {}
+    /// Loop function for task for test_harness_reports_leaks.
+    fn test_harness_reports_leaks_loop_fn() {}
+    /// Stop function for task for test_harness_reports_leaks.
+    fn test_harness_reports_leaks_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that the testing harness reports acquired timers and unfinished tasks and cleans them up.
+    fn test_harness_reports_leaks() {
+        let harness = TestHarness::new();
+        std::mem::forget(
+            Timer::get_timer(1)
+                .expect("The timer is already active or a timer with this index does not exist."),
+        );
+        let id = TaskManager::add_named_task(
+            "test-leaked-task",
+            test_harness_reports_leaks_setup_fn,
+            test_harness_reports_leaks_loop_fn,
+            test_harness_reports_leaks_stop_condition_fn,
+        );
+        let report = harness.finish();
+        assert!(!report.is_clean());
+        assert_eq!(report.acquired_timers, [1]);
+        assert_eq!(report.unfinished_tasks, [id]);
+
+        let report = TestHarness::new().finish();
+        assert!(report.is_clean());
+    }
+
+    /// Number of loop calls of root task for test_task_hierarchy_task_manager.
+    static TEST_TASK_HIERARCHY_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Exit hook calls for test_task_hierarchy_task_manager.
+    static TEST_TASK_HIERARCHY_TASK_MANAGER_EXITS: Mutex<Vec<(TaskIdType, ExitReason)>> =
+        Mutex::new(Vec::new());
+    /// Setup function for tasks for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_setup_fn() {}
+    /// Loop function for root task for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_root_loop_fn() {
+        TEST_TASK_HIERARCHY_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for root task for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_root_stop_condition_fn() -> bool {
+        TEST_TASK_HIERARCHY_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 2
+    }
+    /// Loop function for descendant tasks for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_loop_fn() {}
+    /// Stop function for descendant tasks for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Exit hook for test_task_hierarchy_task_manager.
+    fn test_task_hierarchy_task_manager_exit_hook(id: TaskIdType, reason: ExitReason) {
+        TEST_TASK_HIERARCHY_TASK_MANAGER_EXITS
+            .lock()
+            .unwrap()
+            .push((id, reason));
+    }
+    #[test]
+    #[sequential]
+    /// Tests that completion of root task completes all its descendants except the detached one.
+    fn test_task_hierarchy_task_manager() {
+        let harness = TestHarness::new();
+        let root = TaskManager::add_restartable_task(
+            test_task_hierarchy_task_manager_setup_fn,
+            test_task_hierarchy_task_manager_root_loop_fn,
+            test_task_hierarchy_task_manager_root_stop_condition_fn,
+            RestartPolicy::Never,
+        );
+        let spawn = |parent| {
+            let id = TaskManager::spawn_child(
+                parent,
+                test_task_hierarchy_task_manager_setup_fn,
+                test_task_hierarchy_task_manager_loop_fn,
+                test_task_hierarchy_task_manager_stop_condition_fn,
+            )
+            .unwrap();
+            TaskManager::set_exit_hook(id, test_task_hierarchy_task_manager_exit_hook);
+            id
+        };
+        TaskManager::set_exit_hook(root, test_task_hierarchy_task_manager_exit_hook);
+        let child = spawn(root);
+        let grandchild = spawn(child);
+        let detached = spawn(child);
+        let adopted = spawn(detached);
+        assert!(TaskManager::detach(detached));
+        assert!(TaskManager::adopt(adopted, grandchild));
+        // Task can not become descendant of itself.
+        assert!(!TaskManager::adopt(root, adopted));
+        assert_eq!(
+            TaskManager::spawn_child(
+                TaskIdType::MAX,
+                test_task_hierarchy_task_manager_setup_fn,
+                test_task_hierarchy_task_manager_loop_fn,
+                test_task_hierarchy_task_manager_stop_condition_fn,
+            ),
+            None
+        );
+
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            *TEST_TASK_HIERARCHY_TASK_MANAGER_EXITS.lock().unwrap(),
+            [
+                (root, ExitReason::StopCondition),
+                (child, ExitReason::ParentFinished),
+                (grandchild, ExitReason::ParentFinished),
+                (adopted, ExitReason::ParentFinished),
+            ]
+        );
+        assert_eq!(ExitReason::ParentFinished.code(), -1);
+        let mut dump = String::new();
+        dump_state(&mut dump).unwrap();
+        assert!(dump.contains(&format!(
+            "task id={} name=task-{} setup_completed=true restarts=0 parent={}\n",
+            grandchild, grandchild, child
+        )));
+
+        let report = harness.finish();
+        assert_eq!(report.unfinished_tasks, [detached]);
+    }
+
+    /// Identifier of the task, that terminates itself, for test_spawn_child_of_finished_task_manager.
+    static TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_TERMINATED: Mutex<TaskIdType> = Mutex::new(0);
+    /// Result of spawning a child of the terminated task for test_spawn_child_of_finished_task_manager.
+    static TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_CHILD: Mutex<Option<Option<TaskIdType>>> =
+        Mutex::new(None);
+    /// Setup function for tasks for test_spawn_child_of_finished_task_manager.
+    fn test_spawn_child_of_finished_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_spawn_child_of_finished_task_manager.
+    fn test_spawn_child_of_finished_task_manager_loop_fn() {}
+    /// Loop function for the task, that terminates itself and spawns a child,
+    /// for test_spawn_child_of_finished_task_manager.
+    fn test_spawn_child_of_finished_task_manager_terminated_loop_fn() {
+        let id = *TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_TERMINATED
+            .lock()
+            .unwrap();
+        assert_eq!(TaskManager::terminate_current_task(), Ok(()));
+        *TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_CHILD
+            .lock()
+            .unwrap() = Some(TaskManager::spawn_child(
+            id,
+            test_spawn_child_of_finished_task_manager_setup_fn,
+            test_spawn_child_of_finished_task_manager_loop_fn,
+            test_spawn_child_of_finished_task_manager_stop_condition_fn,
+        ));
+    }
+    /// Stop function for tasks for test_spawn_child_of_finished_task_manager.
+    fn test_spawn_child_of_finished_task_manager_stop_condition_fn() -> bool {
+        true
+    }
+    /// Stop function for the task, that terminates itself, for test_spawn_child_of_finished_task_manager.
+    fn test_spawn_child_of_finished_task_manager_terminated_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that finished and terminated tasks can not get children, which would never be completed.
+    fn test_spawn_child_of_finished_task_manager() {
+        let harness = TestHarness::new();
+        let finished = TaskManager::add_task_with_id(
+            test_spawn_child_of_finished_task_manager_setup_fn,
+            test_spawn_child_of_finished_task_manager_loop_fn,
+            test_spawn_child_of_finished_task_manager_stop_condition_fn,
+        );
+        let terminated = TaskManager::add_task_with_id(
+            test_spawn_child_of_finished_task_manager_setup_fn,
+            test_spawn_child_of_finished_task_manager_terminated_loop_fn,
+            test_spawn_child_of_finished_task_manager_terminated_stop_condition_fn,
+        );
+        *TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_TERMINATED
+            .lock()
+            .unwrap() = terminated;
+
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            *TEST_SPAWN_CHILD_OF_FINISHED_TASK_MANAGER_CHILD
+                .lock()
+                .unwrap(),
+            Some(None)
+        );
+        assert_eq!(
+            TaskManager::spawn_child(
+                finished,
+                test_spawn_child_of_finished_task_manager_setup_fn,
+                test_spawn_child_of_finished_task_manager_loop_fn,
+                test_spawn_child_of_finished_task_manager_stop_condition_fn,
+            ),
+            None
+        );
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Indicator whether the sleeper is woken up with priority for test_prioritized_wake_task_manager.
+    static TEST_PRIORITIZED_WAKE_TASK_MANAGER_PRIORITIZED: AtomicBool = AtomicBool::new(false);
+    /// Identifier of the sleeper for test_prioritized_wake_task_manager.
+    static TEST_PRIORITIZED_WAKE_TASK_MANAGER_SLEEPER: Mutex<TaskIdType> = Mutex::new(0);
+    /// Identifiers of executed tasks in order of execution for test_prioritized_wake_task_manager.
+    static TEST_PRIORITIZED_WAKE_TASK_MANAGER_ORDER: Mutex<Vec<TaskIdType>> =
+        Mutex::new(Vec::new());
+    /// Setup function for tasks for test_prioritized_wake_task_manager.
+    fn test_prioritized_wake_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_prioritized_wake_task_manager.
+    fn test_prioritized_wake_task_manager_loop_fn() {}
+    /// Loop function for waking task for test_prioritized_wake_task_manager.
+    /// Wakes up the sleeper and spends 1 ms on the virtual clock.
+    fn test_prioritized_wake_task_manager_waker_loop_fn() {
+        let sleeper = *TEST_PRIORITIZED_WAKE_TASK_MANAGER_SLEEPER.lock().unwrap();
+        if TEST_PRIORITIZED_WAKE_TASK_MANAGER_PRIORITIZED.load(Ordering::Relaxed) {
+            TaskManager::wake_up_task_prioritized(sleeper);
+        } else {
+            TaskManager::wake_up_task(sleeper);
+        }
+        delay_ms(1);
+    }
+    /// Stop function for waking task for test_prioritized_wake_task_manager.
+    fn test_prioritized_wake_task_manager_waker_stop_condition_fn() -> bool {
+        TEST_PRIORITIZED_WAKE_TASK_MANAGER_ORDER
+            .lock()
+            .unwrap()
+            .len()
+            > 5
+    }
+    /// Stop function for tasks for test_prioritized_wake_task_manager.
+    fn test_prioritized_wake_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Post-iteration hook for test_prioritized_wake_task_manager.
+    fn test_prioritized_wake_task_manager_post_hook(id: TaskIdType, _loop_called: bool) {
+        TEST_PRIORITIZED_WAKE_TASK_MANAGER_ORDER
+            .lock()
+            .unwrap()
+            .push(id);
+    }
+    #[test]
+    #[sequential]
+    /// Tests that prioritized wake runs the task on the next step, then round-robin order continues,
+    /// and that wake latency is recorded in steps and microseconds of the virtual clock.
+    fn test_prioritized_wake_task_manager() {
+        for prioritized in [true, false] {
+            let harness = TestHarness::new();
+            TEST_PRIORITIZED_WAKE_TASK_MANAGER_PRIORITIZED.store(prioritized, Ordering::Relaxed);
+            TEST_PRIORITIZED_WAKE_TASK_MANAGER_ORDER
+                .lock()
+                .unwrap()
+                .clear();
+            let add = || {
+                TaskManager::add_task_with_id(
+                    test_prioritized_wake_task_manager_setup_fn,
+                    test_prioritized_wake_task_manager_loop_fn,
+                    test_prioritized_wake_task_manager_stop_condition_fn,
+                )
+            };
+            let waker = TaskManager::add_task_with_id(
+                test_prioritized_wake_task_manager_setup_fn,
+                test_prioritized_wake_task_manager_waker_loop_fn,
+                test_prioritized_wake_task_manager_waker_stop_condition_fn,
+            );
+            let (first, second, third, sleeper) = (add(), add(), add(), add());
+            *TEST_PRIORITIZED_WAKE_TASK_MANAGER_SLEEPER.lock().unwrap() = sleeper;
+            TaskManager::put_to_sleep(sleeper);
+            assert_eq!(
+                TaskManager::try_wake_up_task_prioritized(first),
+                Err(TaskError::InvalidState)
+            );
+            assert_eq!(
+                TaskManager::try_wake_up_task_prioritized(TaskIdType::MAX),
+                Err(TaskError::NotFound)
+            );
+            TaskManager::set_iteration_hooks(
+                None,
+                Some(test_prioritized_wake_task_manager_post_hook),
+            );
+
+            // Setup of all tasks, skipped sleeper, the waker wakes up the sleeper and four more steps.
+            for _ in 0..10 {
+                TaskManager::run_one_iteration();
+            }
+            let order = TEST_PRIORITIZED_WAKE_TASK_MANAGER_ORDER
+                .lock()
+                .unwrap()
+                .clone();
+            let info = TaskManager::get_task_info(sleeper).unwrap();
+            if prioritized {
+                assert_eq!(order[5..], [waker, sleeper, first, second, third]);
+                assert_eq!(info.wake_latency_iterations, 1);
+            } else {
+                assert_eq!(order[5..], [waker, first, second, third, sleeper]);
+                assert_eq!(info.wake_latency_iterations, 4);
+            }
+            assert_eq!(info.wake_latency_us, 1000);
+            let report = harness.finish();
+            assert_eq!(report.unfinished_tasks.len(), 5);
+        }
+    }
+
+    /// Identifiers of the worker and of the deleted task for test_join_task_manager.
+    static TEST_JOIN_TASK_MANAGER_IDS: Mutex<(TaskIdType, TaskIdType)> = Mutex::new((0, 0));
+    /// Number of loop calls of the worker for test_join_task_manager.
+    static TEST_JOIN_TASK_MANAGER_WORKER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Values of the worker counter seen by loop calls of supervisors for test_join_task_manager.
+    static TEST_JOIN_TASK_MANAGER_SEEN: Mutex<[Vec<u32>; 2]> = Mutex::new([Vec::new(), Vec::new()]);
+    /// Setup function for tasks for test_join_task_manager.
+    fn test_join_task_manager_setup_fn() {}
+    /// Loop function for the worker for test_join_task_manager.
+    fn test_join_task_manager_worker_loop_fn() {
+        TEST_JOIN_TASK_MANAGER_WORKER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for the worker for test_join_task_manager.
+    fn test_join_task_manager_worker_stop_condition_fn() -> bool {
+        TEST_JOIN_TASK_MANAGER_WORKER_COUNTER.load(Ordering::Relaxed) >= 50
+    }
+    /// Loop call of the supervisor with the specified number for test_join_task_manager.
+    /// The first call waits for the worker, the second one sees that it is completed.
+    fn test_join_task_manager_supervise(supervisor: usize) {
+        let (worker, deleted) = *TEST_JOIN_TASK_MANAGER_IDS.lock().unwrap();
+        let calls = {
+            let mut seen = TEST_JOIN_TASK_MANAGER_SEEN.lock().unwrap();
+            seen[supervisor].push(TEST_JOIN_TASK_MANAGER_WORKER_COUNTER.load(Ordering::Relaxed));
+            seen[supervisor].len()
+        };
+        assert_eq!(
+            TaskManager::wait_for_termination(deleted),
+            JoinHandleState::AlreadyTerminated
+        );
+        let current =
+            TaskManager::find_task_by_name(["supervisor-0", "supervisor-1"][supervisor]).unwrap();
+        assert_eq!(
+            TaskManager::try_wait_for_termination(current),
+            Err(TaskError::InvalidState)
+        );
+        let expected = if calls == 1 {
+            JoinHandleState::Waiting
+        } else {
+            JoinHandleState::AlreadyTerminated
+        };
+        assert_eq!(TaskManager::wait_for_termination(worker), expected);
+    }
+    /// Loop function for the first supervisor for test_join_task_manager.
+    fn test_join_task_manager_supervisor0_loop_fn() {
+        test_join_task_manager_supervise(0);
+    }
+    /// Loop function for the second supervisor for test_join_task_manager.
+    fn test_join_task_manager_supervisor1_loop_fn() {
+        test_join_task_manager_supervise(1);
+    }
+    /// Stop function for supervisors for test_join_task_manager.
+    fn test_join_task_manager_supervisor_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that tasks waiting for termination of the worker sleep while it works
+    /// and wake up exactly when it is completed, and that waiting for a deleted task returns immediately.
+    fn test_join_task_manager() {
+        let harness = TestHarness::new();
+        let supervisors = [
+            TaskManager::add_named_task(
+                "supervisor-0",
+                test_join_task_manager_setup_fn,
+                test_join_task_manager_supervisor0_loop_fn,
+                test_join_task_manager_supervisor_stop_condition_fn,
+            ),
+            TaskManager::add_named_task(
+                "supervisor-1",
+                test_join_task_manager_setup_fn,
+                test_join_task_manager_supervisor1_loop_fn,
+                test_join_task_manager_supervisor_stop_condition_fn,
+            ),
+        ];
+        let worker = TaskManager::add_task_with_id(
+            test_join_task_manager_setup_fn,
+            test_join_task_manager_worker_loop_fn,
+            test_join_task_manager_worker_stop_condition_fn,
+        );
+        let deleted = TaskManager::add_task_with_id(
+            test_join_task_manager_setup_fn,
+            test_join_task_manager_worker_loop_fn,
+            test_join_task_manager_worker_stop_condition_fn,
+        );
+        TaskManager::delete_task(deleted);
+        *TEST_JOIN_TASK_MANAGER_IDS.lock().unwrap() = (worker, deleted);
+        // Only tasks can wait.
+        assert_eq!(
+            TaskManager::try_wait_for_termination(worker),
+            Err(TaskError::NotFound)
+        );
+
+        for _ in 0..20 {
+            TaskManager::run_one_iteration();
+        }
+        for supervisor in supervisors {
+            assert_eq!(
+                TaskManager::get_task_info(supervisor).unwrap().status,
+                TaskStatus::Sleeping
+            );
+        }
+        TaskManager::test_start_task_manager();
+
+        // Supervisors sleep across all iterations of the worker and wake up, when it is completed.
+        for seen in TEST_JOIN_TASK_MANAGER_SEEN.lock().unwrap().iter() {
+            assert_eq!(seen[..2], [0, 50]);
+        }
+        assert_eq!(
+            TaskManager::get_task_info(worker).unwrap().status,
+            TaskStatus::Finished
+        );
+        let report = harness.finish();
+        assert_eq!(report.unfinished_tasks, supervisors);
+    }
+
+    /// Identifiers of two tasks, that wait for each other, for test_deadlock_task_manager.
+    static TEST_DEADLOCK_TASK_MANAGER_IDS: Mutex<(TaskIdType, TaskIdType)> = Mutex::new((0, 0));
+    /// Reported deadlocks for test_deadlock_task_manager.
+    static TEST_DEADLOCK_TASK_MANAGER_REPORTS: Mutex<Vec<DeadlockReport>> = Mutex::new(Vec::new());
+    /// Setup function for tasks for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_setup_fn() {}
+    /// Loop function for the first task, that waits for the second one, for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_first_loop_fn() {
+        let (_, second) = *TEST_DEADLOCK_TASK_MANAGER_IDS.lock().unwrap();
+        TaskManager::wait_for_termination(second);
+    }
+    /// Loop function for the second task, that waits for the first one, for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_second_loop_fn() {
+        let (first, _) = *TEST_DEADLOCK_TASK_MANAGER_IDS.lock().unwrap();
+        TaskManager::wait_for_termination(first);
+    }
+    /// Loop function for the task, that sleeps until a deadline, for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_sleeper_loop_fn() {}
+    /// Stop function for tasks for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Deadlock hook for test_deadlock_task_manager.
+    fn test_deadlock_task_manager_hook(report: &DeadlockReport) {
+        TEST_DEADLOCK_TASK_MANAGER_REPORTS
+            .lock()
+            .unwrap()
+            .push(report.clone());
+    }
+    #[test]
+    #[sequential]
+    /// Tests that mutual waiting for termination is reported once, and is not reported
+    /// while another task sleeps until a deadline.
+    fn test_deadlock_task_manager() {
+        for with_deadline in [true, false] {
+            let harness = TestHarness::new();
+            TEST_DEADLOCK_TASK_MANAGER_REPORTS.lock().unwrap().clear();
+            TaskManager::on_deadlock(Some(test_deadlock_task_manager_hook));
+            let first = TaskManager::add_task_with_id(
+                test_deadlock_task_manager_setup_fn,
+                test_deadlock_task_manager_first_loop_fn,
+                test_deadlock_task_manager_stop_condition_fn,
+            );
+            let second = TaskManager::add_task_with_id(
+                test_deadlock_task_manager_setup_fn,
+                test_deadlock_task_manager_second_loop_fn,
+                test_deadlock_task_manager_stop_condition_fn,
+            );
+            let sleeper = TaskManager::add_task_with_id(
+                test_deadlock_task_manager_setup_fn,
+                test_deadlock_task_manager_sleeper_loop_fn,
+                test_deadlock_task_manager_stop_condition_fn,
+            );
+            *TEST_DEADLOCK_TASK_MANAGER_IDS.lock().unwrap() = (first, second);
+            if with_deadline {
+                // Virtual clock does not advance without delays, so the sleeper never wakes up.
+                TaskManager::sleep_for(sleeper, Duration::from_secs(3600));
+            } else {
+                TaskManager::put_to_sleep(sleeper);
+            }
+            TaskManager::test_start_task_manager();
+
+            let reports = TEST_DEADLOCK_TASK_MANAGER_REPORTS.lock().unwrap().clone();
+            if with_deadline {
+                assert!(reports.is_empty());
+            } else {
+                assert_eq!(
+                    reports,
+                    [DeadlockReport {
+                        tasks: vec![
+                            (first, WaitReason::Join(second)),
+                            (second, WaitReason::Join(first))
+                        ]
+                    }]
+                );
+            }
+            harness.finish();
+        }
+    }
+
+    /// Identifiers of executed tasks in order of execution for test_event_wake_task_manager.
+    static TEST_EVENT_WAKE_TASK_MANAGER_ORDER: Mutex<Vec<TaskIdType>> = Mutex::new(Vec::new());
+    /// Setup function for tasks for test_event_wake_task_manager.
+    fn test_event_wake_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_event_wake_task_manager.
+    fn test_event_wake_task_manager_loop_fn() {}
+    /// Stop function for tasks for test_event_wake_task_manager.
+    fn test_event_wake_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Post-iteration hook for test_event_wake_task_manager.
+    fn test_event_wake_task_manager_post_hook(id: TaskIdType, _loop_called: bool) {
+        TEST_EVENT_WAKE_TASK_MANAGER_ORDER.lock().unwrap().push(id);
+    }
+    #[test]
+    #[sequential]
+    /// Tests that task woken up by simulated interrupt runs ahead of other tasks exactly once,
+    /// even if events are repeated before it runs.
+    fn test_event_wake_task_manager() {
+        let harness = TestHarness::new();
+        let add = || {
+            TaskManager::add_task_with_id(
+                test_event_wake_task_manager_setup_fn,
+                test_event_wake_task_manager_loop_fn,
+                test_event_wake_task_manager_stop_condition_fn,
+            )
+        };
+        let (first, second, third, fourth) = (add(), add(), add(), add());
+        TaskManager::set_iteration_hooks(None, Some(test_event_wake_task_manager_post_hook));
+        let run = |steps| {
+            TEST_EVENT_WAKE_TASK_MANAGER_ORDER.lock().unwrap().clear();
+            for _ in 0..steps {
+                TaskManager::run_one_iteration();
+            }
+            TEST_EVENT_WAKE_TASK_MANAGER_ORDER.lock().unwrap().clone()
+        };
+        assert_eq!(run(5), [first, second, third, fourth, first]);
+
+        // Events of a ready task from an interrupt handler.
+        TaskManager::wake_up_task_from_event(fourth);
+        TaskManager::wake_up_task_from_event(fourth);
+        assert_eq!(run(5), [fourth, second, third, fourth, first]);
+        assert_eq!(TaskManager::get_task_info(fourth).unwrap().boosted_wakes, 1);
+
+        // Event of a sleeping task.
+        TaskManager::put_to_sleep(third);
+        assert_eq!(run(4), [second, third, fourth, first]);
+        TaskManager::wake_up_task_from_event(third);
+        assert_eq!(run(4), [third, second, third, fourth]);
+        let info = TaskManager::get_task_info(third).unwrap();
+        assert_eq!((info.boosted_wakes, info.wake_latency_iterations), (1, 1));
+
+        assert_eq!(
+            TaskManager::try_wake_up_task_from_event(TaskIdType::MAX),
+            Err(TaskError::NotFound)
+        );
+        harness.finish();
+    }
+
+    /// Marker, that the task is waiting in its loop, for test_cancel_token_task_manager.
+    static TEST_CANCEL_TOKEN_TASK_MANAGER_WAITING: AtomicBool = AtomicBool::new(false);
+    /// Result of cancellable delay for test_cancel_token_task_manager.
+    static TEST_CANCEL_TOKEN_TASK_MANAGER_RESULT: Mutex<Option<Result<(), TaskError>>> =
+        Mutex::new(None);
+    /// Exit reasons of tasks for test_cancel_token_task_manager.
+    static TEST_CANCEL_TOKEN_TASK_MANAGER_EXITS: Mutex<Vec<(TaskIdType, ExitReason)>> =
+        Mutex::new(Vec::new());
+    /// Setup function for task for test_cancel_token_task_manager.
+    fn test_cancel_token_task_manager_setup_fn() {}
+    /// Loop function for task for test_cancel_token_task_manager.
+    fn test_cancel_token_task_manager_loop_fn() {
+        let token = TaskManager::current_cancel_token().unwrap();
+        TEST_CANCEL_TOKEN_TASK_MANAGER_WAITING.store(true, Ordering::Relaxed);
+        // The delay never ends by itself, only termination of the task stops it.
+        let result = delay_ms_cancellable(u64::MAX, &token);
+        *TEST_CANCEL_TOKEN_TASK_MANAGER_RESULT.lock().unwrap() = Some(result);
+    }
+    /// Stop function for task for test_cancel_token_task_manager.
+    fn test_cancel_token_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Exit hook for test_cancel_token_task_manager.
+    fn test_cancel_token_task_manager_exit_hook(id: TaskIdType, reason: ExitReason) {
+        TEST_CANCEL_TOKEN_TASK_MANAGER_EXITS
+            .lock()
+            .unwrap()
+            .push((id, reason));
+    }
+    #[test]
+    #[sequential]
+    /// Tests that termination of a task by simulated interrupt cancels its waiting in the loop.
+    fn test_cancel_token_task_manager() {
+        let harness = TestHarness::new();
+        assert!(TaskManager::current_cancel_token().is_none());
+        let task = TaskManager::add_task_with_id(
+            test_cancel_token_task_manager_setup_fn,
+            test_cancel_token_task_manager_loop_fn,
+            test_cancel_token_task_manager_stop_condition_fn,
+        );
+        TaskManager::set_exit_hook(task, test_cancel_token_task_manager_exit_hook);
+        let interrupt = std::thread::spawn(move || {
+            while !TEST_CANCEL_TOKEN_TASK_MANAGER_WAITING.load(Ordering::Relaxed) {
+                std::thread::yield_now();
+            }
+            TaskManager::terminate_task(task)
+        });
+
+        // The first iteration calls setup function, the second one waits in loop function.
+        TaskManager::run_one_iteration();
+        TaskManager::run_one_iteration();
+
+        assert_eq!(interrupt.join().unwrap(), Ok(()));
+        assert_eq!(
+            *TEST_CANCEL_TOKEN_TASK_MANAGER_RESULT.lock().unwrap(),
+            Some(Err(TaskError::Cancelled))
+        );
+        assert_eq!(
+            *TEST_CANCEL_TOKEN_TASK_MANAGER_EXITS.lock().unwrap(),
+            [(task, ExitReason::Terminated)]
+        );
+        assert_eq!(TaskError::Cancelled.error_code(), -144);
+        harness.finish();
+    }
+
+    /// Number of loop calls of sleeping task for test_sleep_wake_delete_task_manager.
+    static TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SLEEPER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of loop calls of deleted task for test_sleep_wake_delete_task_manager.
+    static TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_DELETED_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Error of deleting task by itself for test_sleep_wake_delete_task_manager.
+    static TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SELF_DELETE: Mutex<Option<Result<(), TaskError>>> =
+        Mutex::new(None);
+    /// Setup function for tasks for test_sleep_wake_delete_task_manager.
+    fn test_sleep_wake_delete_task_manager_setup_fn() {}
+    /// Loop function for sleeping task for test_sleep_wake_delete_task_manager.
+    fn test_sleep_wake_delete_task_manager_sleeper_loop_fn() {
+        TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SLEEPER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for sleeping task for test_sleep_wake_delete_task_manager.
+    fn test_sleep_wake_delete_task_manager_sleeper_stop_condition_fn() -> bool {
+        TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SLEEPER_COUNTER.load(Ordering::Relaxed) >= 3
+    }
+    /// Loop function for deleted task for test_sleep_wake_delete_task_manager.
+    fn test_sleep_wake_delete_task_manager_deleted_loop_fn() {
+        TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_DELETED_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let id = TaskManager::find_task_by_name("test-deleted-task").unwrap();
+        *TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SELF_DELETE
+            .lock()
+            .unwrap() = Some(TaskManager::try_delete_task(id));
+    }
+    /// Stop function for deleted task for test_sleep_wake_delete_task_manager.
+    fn test_sleep_wake_delete_task_manager_deleted_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that sleeping task is not executed until it is woken up, deleted task is not executed at all,
+    /// and every task state error is reported.
+    fn test_sleep_wake_delete_task_manager() {
+        let harness = TestHarness::new();
+        let sleeper = TaskManager::add_task_with_id(
+            test_sleep_wake_delete_task_manager_setup_fn,
+            test_sleep_wake_delete_task_manager_sleeper_loop_fn,
+            test_sleep_wake_delete_task_manager_sleeper_stop_condition_fn,
+        );
+        let deleted = TaskManager::add_named_task(
+            "test-deleted-task",
+            test_sleep_wake_delete_task_manager_setup_fn,
+            test_sleep_wake_delete_task_manager_deleted_loop_fn,
+            test_sleep_wake_delete_task_manager_deleted_stop_condition_fn,
+        );
+
+        assert_eq!(TaskManager::try_put_to_sleep(sleeper), Ok(()));
+        assert_eq!(
+            TaskManager::try_put_to_sleep(sleeper),
+            Err(TaskError::InvalidState)
+        );
+        assert_eq!(
+            TaskManager::try_wake_up_task(deleted),
+            Err(TaskError::InvalidState)
+        );
+        assert_eq!(
+            TaskManager::try_put_to_sleep(TaskIdType::MAX),
+            Err(TaskError::NotFound)
+        );
+        assert_eq!(
+            TaskManager::try_wake_up_task(TaskIdType::MAX),
+            Err(TaskError::NotFound)
+        );
+        assert_eq!(
+            TaskManager::try_delete_task(TaskIdType::MAX),
+            Err(TaskError::NotFound)
+        );
+
+        // Deleted task runs its loop once and fails to delete itself.
+        for _ in 0..4 {
+            TaskManager::run_one_iteration();
+        }
+        assert_eq!(
+            *TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SELF_DELETE
+                .lock()
+                .unwrap(),
+            Some(Err(TaskError::CurrentlyRunning))
+        );
+        assert_eq!(
+            TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_DELETED_COUNTER.load(Ordering::Relaxed),
+            1
+        );
+        TaskManager::delete_task(deleted);
+        assert_eq!(
+            TaskManager::run_one_iteration().next_deadline_us,
+            None,
+            "only sleeping task is left"
+        );
+        assert_eq!(
+            TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SLEEPER_COUNTER.load(Ordering::Relaxed),
+            0
+        );
+
+        TaskManager::wake_up_task(sleeper);
+        TaskManager::test_start_task_manager();
+        assert_eq!(
+            TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_SLEEPER_COUNTER.load(Ordering::Relaxed),
+            3
+        );
+        assert_eq!(
+            TaskManager::try_put_to_sleep(sleeper),
+            Err(TaskError::InvalidState)
+        );
+        assert_eq!(
+            TEST_SLEEP_WAKE_DELETE_TASK_MANAGER_DELETED_COUNTER.load(Ordering::Relaxed),
+            1
+        );
+
+        let error = MartosError::from(TaskError::CurrentlyRunning);
+        assert_eq!(error.error_code(), -142);
+        assert_eq!(TaskError::NotFound.error_code(), -140);
+        assert_eq!(TaskError::InvalidState.error_code(), -141);
+        assert_eq!(error.to_string(), "task error: task is being executed");
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Number of loop calls of timed sleeping task for test_sleep_for_task_manager.
+    static TEST_SLEEP_FOR_TASK_MANAGER_TIMED_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of loop calls of forever sleeping task for test_sleep_for_task_manager.
+    static TEST_SLEEP_FOR_TASK_MANAGER_FOREVER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of loop calls of early woken task for test_sleep_for_task_manager.
+    static TEST_SLEEP_FOR_TASK_MANAGER_WOKEN_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for tasks for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_setup_fn() {}
+    /// Loop function for timed sleeping task for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_timed_loop_fn() {
+        TEST_SLEEP_FOR_TASK_MANAGER_TIMED_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for timed sleeping task for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_timed_stop_condition_fn() -> bool {
+        TEST_SLEEP_FOR_TASK_MANAGER_TIMED_COUNTER.load(Ordering::Relaxed) >= 2
+    }
+    /// Loop function for forever sleeping task for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_forever_loop_fn() {
+        TEST_SLEEP_FOR_TASK_MANAGER_FOREVER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Loop function for early woken task for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_woken_loop_fn() {
+        TEST_SLEEP_FOR_TASK_MANAGER_WOKEN_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for infinite tasks for test_sleep_for_task_manager.
+    fn test_sleep_for_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that task sleeping for a duration is woken up by the virtual clock of Mok,
+    /// while task sleeping forever is not, and that explicit wake-up cancels the deadline.
+    fn test_sleep_for_task_manager() {
+        let harness = TestHarness::new();
+        let timed = TaskManager::add_task_with_id(
+            test_sleep_for_task_manager_setup_fn,
+            test_sleep_for_task_manager_timed_loop_fn,
+            test_sleep_for_task_manager_timed_stop_condition_fn,
+        );
+        let forever = TaskManager::add_task_with_id(
+            test_sleep_for_task_manager_setup_fn,
+            test_sleep_for_task_manager_forever_loop_fn,
+            test_sleep_for_task_manager_stop_condition_fn,
+        );
+        let woken = TaskManager::add_task_with_id(
+            test_sleep_for_task_manager_setup_fn,
+            test_sleep_for_task_manager_woken_loop_fn,
+            test_sleep_for_task_manager_stop_condition_fn,
+        );
+        TaskManager::sleep_for(timed, Duration::from_millis(10));
+        TaskManager::put_to_sleep(forever);
+        TaskManager::sleep_for(woken, Duration::from_secs(1));
+        assert_eq!(
+            TaskManager::try_sleep_for(timed, Duration::from_millis(10)),
+            Err(TaskError::InvalidState)
+        );
+        assert_eq!(
+            TaskManager::try_sleep_for(TaskIdType::MAX, Duration::from_millis(10)),
+            Err(TaskError::NotFound)
+        );
+        // System clock of Mok is the last timer.
+        assert_eq!(
+            list_timers().last().unwrap().state,
+            TimerSlotState::Reserved("clock")
+        );
+
+        assert_eq!(
+            TaskManager::run_one_iteration().next_deadline_us,
+            Some(10_000)
+        );
+        delay_ms(4);
+        assert_eq!(
+            TaskManager::run_one_iteration().next_deadline_us,
+            Some(6_000)
+        );
+        delay_ms(6);
+        assert_eq!(TaskManager::run_one_iteration().next_deadline_us, Some(0));
+        for _ in 0..9 {
+            TaskManager::run_one_iteration();
+        }
+        assert_eq!(
+            TEST_SLEEP_FOR_TASK_MANAGER_TIMED_COUNTER.load(Ordering::Relaxed),
+            2
+        );
+        assert_eq!(
+            TEST_SLEEP_FOR_TASK_MANAGER_WOKEN_COUNTER.load(Ordering::Relaxed),
+            0
+        );
+
+        TaskManager::wake_up_task(woken);
+        // The first turn of the woken task is its setup, the second one is its loop.
+        for _ in 0..6 {
+            TaskManager::run_one_iteration();
+        }
+        assert!(TEST_SLEEP_FOR_TASK_MANAGER_WOKEN_COUNTER.load(Ordering::Relaxed) > 0);
+        assert_eq!(TaskManager::run_one_iteration().next_deadline_us, Some(0));
+        TaskManager::put_to_sleep(woken);
+        // Neither task has deadline anymore, so task manager is idle.
+        assert_eq!(TaskManager::run_one_iteration().next_deadline_us, None);
+        delay_ms(2000);
+        TaskManager::test_start_task_manager();
+        assert_eq!(
+            TEST_SLEEP_FOR_TASK_MANAGER_FOREVER_COUNTER.load(Ordering::Relaxed),
+            0
+        );
+        TaskManager::for_each_task(|task| {
+            assert_eq!(task.is_sleeping(), task.id() != timed);
+        });
+        TaskManager::delete_task(forever);
+        TaskManager::delete_task(woken);
+        assert!(harness.finish().is_clean());
+
+        // System clock is reserved by initialization and can not be acquired by user.
+        let harness = TestHarness::new();
+        let clock = list_timers()
+            .iter()
+            .find(|slot| slot.state == TimerSlotState::Reserved("clock"))
+            .map(|slot| slot.index)
+            .expect("Host port has system clock.");
+        assert_eq!(
+            Timer::get_timer(clock).err(),
+            Some(TimerError::ReservedBySystem("clock"))
+        );
+        assert!(harness.finish().is_clean());
+
+        // Sleep for a duration is not possible before the system is initialized.
+        shutdown_system().unwrap();
+        let task = TaskManager::add_task_with_id(
+            test_sleep_for_task_manager_setup_fn,
+            test_sleep_for_task_manager_forever_loop_fn,
+            test_sleep_for_task_manager_stop_condition_fn,
+        );
+        assert_eq!(
+            TaskManager::try_sleep_for(task, Duration::from_millis(10)),
+            Err(TaskError::ClockUnavailable)
+        );
+        assert_eq!(TaskError::ClockUnavailable.error_code(), -143);
+        TaskManager::delete_task(task);
+    }
+
+    /// Snapshot of tasks taken by inspecting task for test_task_info_task_manager.
+    static TEST_TASK_INFO_TASK_MANAGER_SNAPSHOT: Mutex<Vec<TaskInfo>> = Mutex::new(Vec::new());
+    /// Setup function for tasks for test_task_info_task_manager.
+    fn test_task_info_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_task_info_task_manager.
+    fn test_task_info_task_manager_loop_fn() {}
+    /// Stop function for infinite tasks for test_task_info_task_manager.
+    fn test_task_info_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Stop function for finite task for test_task_info_task_manager.
+    fn test_task_info_task_manager_finished_stop_condition_fn() -> bool {
+        true
+    }
+    /// Loop function for inspecting task for test_task_info_task_manager.
+    fn test_task_info_task_manager_inspector_loop_fn() {
+        *TEST_TASK_INFO_TASK_MANAGER_SNAPSHOT.lock().unwrap() = TaskManager::list_tasks();
+    }
+    #[test]
+    #[sequential]
+    /// Tests that task snapshot taken by a running task reports status of every task.
+    fn test_task_info_task_manager() {
+        let harness = TestHarness::new();
+        let add = |stop_condition_fn: fn() -> bool| {
+            TaskManager::add_task_with_id(
+                test_task_info_task_manager_setup_fn,
+                test_task_info_task_manager_loop_fn,
+                stop_condition_fn,
+            )
+        };
+        let ready = add(test_task_info_task_manager_stop_condition_fn);
+        let sleeping = add(test_task_info_task_manager_stop_condition_fn);
+        let finished = add(test_task_info_task_manager_finished_stop_condition_fn);
+        let inspector = TaskManager::add_task_with_id(
+            test_task_info_task_manager_setup_fn,
+            test_task_info_task_manager_inspector_loop_fn,
+            test_task_info_task_manager_stop_condition_fn,
+        );
+        TaskManager::put_to_sleep(sleeping);
+        // The inspector takes snapshot on the second round, when its setup is completed.
+        for _ in 0..8 {
+            TaskManager::run_one_iteration();
+        }
+
+        let info = |id, status| TaskInfo {
+            id,
+            priority: 0,
+            status,
+            restart_count: 0,
+            flags: TaskFlags::NONE,
+            name: [0; TASK_NAME_MAX_LEN],
+            inbox_dropped: 0,
+            wake_latency_iterations: 0,
+            boosted_wakes: 0,
+            wake_latency_us: 0,
+        };
+        assert_eq!(
+            *TEST_TASK_INFO_TASK_MANAGER_SNAPSHOT.lock().unwrap(),
+            [
+                info(ready, TaskStatus::Ready),
+                info(sleeping, TaskStatus::Sleeping),
+                info(finished, TaskStatus::Finished),
+                info(inspector, TaskStatus::Running),
+            ]
+        );
+        assert_eq!(
+            TaskManager::get_task_info(inspector),
+            Some(info(inspector, TaskStatus::Ready))
+        );
+        assert_eq!(TaskManager::get_task_info(TaskIdType::MAX), None);
+        assert_eq!(TaskManager::list_tasks().len(), 4);
+
+        for id in [ready, sleeping, inspector] {
+            TaskManager::delete_task(id);
+        }
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Number of loop calls of self-terminating task for test_terminate_task_manager.
+    static TEST_TERMINATE_TASK_MANAGER_SELF_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of loop calls of terminated task for test_terminate_task_manager.
+    static TEST_TERMINATE_TASK_MANAGER_VICTIM_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Identifier of the task, that is terminated by another task, for test_terminate_task_manager.
+    static TEST_TERMINATE_TASK_MANAGER_VICTIM: Mutex<TaskIdType> = Mutex::new(0);
+    /// Exit hook calls for test_terminate_task_manager.
+    static TEST_TERMINATE_TASK_MANAGER_EXITS: Mutex<Vec<(TaskIdType, ExitReason)>> =
+        Mutex::new(Vec::new());
+    /// Setup function for tasks for test_terminate_task_manager.
+    fn test_terminate_task_manager_setup_fn() {}
+    /// Loop function for self-terminating task for test_terminate_task_manager.
+    /// The task terminates itself in the middle of its second loop iteration.
+    fn test_terminate_task_manager_self_loop_fn() {
+        if TEST_TERMINATE_TASK_MANAGER_SELF_COUNTER.fetch_add(1, Ordering::Relaxed) == 1 {
+            assert_eq!(TaskManager::terminate_current_task(), Ok(()));
+            assert_eq!(
+                TaskManager::terminate_current_task(),
+                Err(TaskError::InvalidState)
+            );
+        }
+    }
+    /// Loop function for terminating task for test_terminate_task_manager.
+    fn test_terminate_task_manager_killer_loop_fn() {
+        let victim = *TEST_TERMINATE_TASK_MANAGER_VICTIM.lock().unwrap();
+        assert_eq!(TaskManager::terminate_task(victim), Ok(()));
+    }
+    /// Loop function for terminated task for test_terminate_task_manager.
+    fn test_terminate_task_manager_victim_loop_fn() {
+        TEST_TERMINATE_TASK_MANAGER_VICTIM_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for infinite tasks for test_terminate_task_manager.
+    fn test_terminate_task_manager_stop_condition_fn() -> bool {
+        false
+    }
+    /// Stop function for terminating task for test_terminate_task_manager.
+    fn test_terminate_task_manager_killer_stop_condition_fn() -> bool {
+        TaskManager::get_task_info(*TEST_TERMINATE_TASK_MANAGER_VICTIM.lock().unwrap()).is_none()
+    }
+    /// Exit hook for test_terminate_task_manager.
+    fn test_terminate_task_manager_exit_hook(id: TaskIdType, reason: ExitReason) {
+        TEST_TERMINATE_TASK_MANAGER_EXITS
+            .lock()
+            .unwrap()
+            .push((id, reason));
+    }
+    #[test]
+    #[sequential]
+    /// Tests that a task can terminate itself in the middle of its loop and another task.
+    fn test_terminate_task_manager() {
+        let harness = TestHarness::new();
+        let self_terminating = TaskManager::add_task_with_id(
+            test_terminate_task_manager_setup_fn,
+            test_terminate_task_manager_self_loop_fn,
+            test_terminate_task_manager_stop_condition_fn,
+        );
+        let killer = TaskManager::add_task_with_id(
+            test_terminate_task_manager_setup_fn,
+            test_terminate_task_manager_killer_loop_fn,
+            test_terminate_task_manager_killer_stop_condition_fn,
+        );
+        let victim = TaskManager::add_task_with_id(
+            test_terminate_task_manager_setup_fn,
+            test_terminate_task_manager_victim_loop_fn,
+            test_terminate_task_manager_stop_condition_fn,
+        );
+        let victim_child = TaskManager::spawn_child(
+            victim,
+            test_terminate_task_manager_setup_fn,
+            test_terminate_task_manager_victim_loop_fn,
+            test_terminate_task_manager_stop_condition_fn,
+        )
+        .unwrap();
+        *TEST_TERMINATE_TASK_MANAGER_VICTIM.lock().unwrap() = victim;
+        for id in [self_terminating, victim, victim_child] {
+            TaskManager::set_exit_hook(id, test_terminate_task_manager_exit_hook);
+        }
+        assert_eq!(
+            TaskManager::terminate_current_task(),
+            Err(TaskError::NotFound)
+        );
+        assert_eq!(
+            TaskManager::terminate_task(TaskIdType::MAX),
+            Err(TaskError::NotFound)
+        );
+
+        TaskManager::test_start_task_manager();
+
+        assert_eq!(
+            TEST_TERMINATE_TASK_MANAGER_SELF_COUNTER.load(Ordering::Relaxed),
+            2
+        );
+        // The victim is terminated by the first loop iteration of the killer before its own loop.
+        assert_eq!(
+            TEST_TERMINATE_TASK_MANAGER_VICTIM_COUNTER.load(Ordering::Relaxed),
+            0
+        );
+        assert_eq!(
+            *TEST_TERMINATE_TASK_MANAGER_EXITS.lock().unwrap(),
+            [
+                (victim, ExitReason::Terminated),
+                (victim_child, ExitReason::ParentFinished),
+                (self_terminating, ExitReason::Terminated),
+            ]
+        );
+        assert_eq!(ExitReason::Terminated.code(), -2);
+        // Terminated tasks are removed, completed ones stay in task manager.
+        let ids: Vec<TaskIdType> = TaskManager::list_tasks()
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, [killer, victim_child]);
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Number of loop calls of spawning task for test_add_delete_stress_task_manager.
+    static TEST_ADD_DELETE_STRESS_TASK_MANAGER_SPAWNER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Number of loop calls of spawned tasks for test_add_delete_stress_task_manager.
+    static TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Identifiers of alive spawned tasks for test_add_delete_stress_task_manager, the oldest first.
+    static TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKERS: Mutex<Vec<TaskIdType>> =
+        Mutex::new(Vec::new());
+    /// Setup function for tasks for test_add_delete_stress_task_manager.
+    fn test_add_delete_stress_task_manager_setup_fn() {}
+    /// Loop function for spawning task for test_add_delete_stress_task_manager.
+    /// Adds a task on every call, puts every second one to sleep and deletes the oldest ones.
+    fn test_add_delete_stress_task_manager_spawner_loop_fn() {
+        let count =
+            TEST_ADD_DELETE_STRESS_TASK_MANAGER_SPAWNER_COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut workers = TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKERS.lock().unwrap();
+        if let Some(&previous) = workers.last() {
+            // Previous task is sleeping only if it was put to sleep on the previous call.
+            let _ = TaskManager::try_wake_up_task(previous);
+        }
+        let worker = TaskManager::add_task_with_id(
+            test_add_delete_stress_task_manager_setup_fn,
+            test_add_delete_stress_task_manager_worker_loop_fn,
+            test_add_delete_stress_task_manager_worker_stop_condition_fn,
+        );
+        workers.push(worker);
+        if count.is_multiple_of(2) {
+            TaskManager::put_to_sleep(worker);
+        }
+        if workers.len() > 4 {
+            TaskManager::delete_task(workers.remove(0));
+        }
+    }
+    /// Stop function for spawning task for test_add_delete_stress_task_manager.
+    fn test_add_delete_stress_task_manager_spawner_stop_condition_fn() -> bool {
+        TEST_ADD_DELETE_STRESS_TASK_MANAGER_SPAWNER_COUNTER.load(Ordering::Relaxed) >= 1000
+    }
+    /// Loop function for spawned tasks for test_add_delete_stress_task_manager.
+    fn test_add_delete_stress_task_manager_worker_loop_fn() {
+        TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for spawned tasks for test_add_delete_stress_task_manager.
+    fn test_add_delete_stress_task_manager_worker_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that tasks can be added, put to sleep and deleted from within a running task for many iterations.
+    fn test_add_delete_stress_task_manager() {
+        let harness = TestHarness::new();
+        let spawner = TaskManager::add_task_with_id(
+            test_add_delete_stress_task_manager_setup_fn,
+            test_add_delete_stress_task_manager_spawner_loop_fn,
+            test_add_delete_stress_task_manager_spawner_stop_condition_fn,
+        );
+        while TaskManager::get_task_info(spawner).unwrap().status != TaskStatus::Finished {
+            TaskManager::run_one_iteration();
+        }
+
+        assert_eq!(
+            TEST_ADD_DELETE_STRESS_TASK_MANAGER_SPAWNER_COUNTER.load(Ordering::Relaxed),
+            1000
+        );
+        assert!(TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKER_COUNTER.load(Ordering::Relaxed) > 0);
+        let workers = TEST_ADD_DELETE_STRESS_TASK_MANAGER_WORKERS.lock().unwrap();
+        let ids: Vec<TaskIdType> = TaskManager::list_tasks()
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids[0], spawner);
+        assert_eq!(ids[1..], workers[..]);
+        for &worker in workers.iter() {
+            TaskManager::delete_task(worker);
+        }
+        assert!(harness.finish().is_clean());
+    }
+
+    /// Number of loop calls of restartable task for test_task_info_fields_task_manager.
+    static TEST_TASK_INFO_FIELDS_TASK_MANAGER_COUNTER: AtomicU32 = AtomicU32::new(0);
+    /// Setup function for tasks for test_task_info_fields_task_manager.
+    fn test_task_info_fields_task_manager_setup_fn() {}
+    /// Loop function for tasks for test_task_info_fields_task_manager.
+    fn test_task_info_fields_task_manager_loop_fn() {
+        TEST_TASK_INFO_FIELDS_TASK_MANAGER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    }
+    /// Stop function for restartable task for test_task_info_fields_task_manager.
+    fn test_task_info_fields_task_manager_stop_condition_fn() -> bool {
+        TEST_TASK_INFO_FIELDS_TASK_MANAGER_COUNTER.load(Ordering::Relaxed) >= 1
+    }
+    /// Stop function for infinite tasks for test_task_info_fields_task_manager.
+    fn test_task_info_fields_task_manager_infinite_stop_condition_fn() -> bool {
+        false
+    }
+    #[test]
+    #[sequential]
+    /// Tests that task snapshot contains restart count, flags, name and inbox drop counter.
+    fn test_task_info_fields_task_manager() {
+        let harness = TestHarness::new();
+        let restartable = TaskManager::add_restartable_task(
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_loop_fn,
+            test_task_info_fields_task_manager_stop_condition_fn,
+            RestartPolicy::MaxTimes(2),
+        );
+        let flagged = TaskManager::add_task_with_flags(
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_infinite_stop_condition_fn,
+            TaskFlags::EXCLUSIVE_BURST,
+            2,
+        );
+        let named = TaskManager::add_named_task(
+            "a-very-long-task-name",
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_infinite_stop_condition_fn,
+        );
+        let with_inbox = TaskManager::add_task_with_inbox(
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_setup_fn,
+            test_task_info_fields_task_manager_infinite_stop_condition_fn,
+            1,
+        );
+        for _ in 0..3 {
+            let _ = TaskManager::send_to(with_inbox, InboxMessage::default());
+        }
+        for _ in 0..40 {
+            TaskManager::run_one_iteration();
+        }
+
+        let restartable = TaskManager::get_task_info(restartable).unwrap();
+        assert_eq!(restartable.restart_count, 2);
+        assert_eq!(restartable.status, TaskStatus::Finished);
+        assert_eq!(restartable.name(), None);
+        assert_eq!(
+            TaskManager::get_task_info(flagged).unwrap().flags,
+            TaskFlags::EXCLUSIVE_BURST
+        );
+        // Long names are truncated to the size of the buffer.
+        assert_eq!(
+            TaskManager::get_task_info(named).unwrap().name(),
+            Some("a-very-long-task")
+        );
+        assert_eq!(
+            TaskManager::get_task_info(with_inbox)
+                .unwrap()
+                .inbox_dropped,
+            2
+        );
+
+        for id in [flagged, named, with_inbox] {
+            TaskManager::delete_task(id);
+        }
+        assert!(harness.finish().is_clean());
+    }
 }
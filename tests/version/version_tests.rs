@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod version_string_tests {
+    use super::super::*;
+
+    #[test]
+    /// Tests that all fields of version information are filled.
+    fn test_version_fields() {
+        let info = version();
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_hash.is_empty());
+        assert!(!info.target.is_empty());
+        assert_eq!(
+            info.build_features.contains(&"network"),
+            cfg!(feature = "network")
+        );
+    }
+
+    #[test]
+    /// Tests that version string is NUL-terminated and safely truncated.
+    fn test_write_version_string() {
+        let mut full = [0xffu8; 256];
+        let len = write_version_string(&mut full);
+        assert!(len > 0 && len < full.len());
+        assert_eq!(full[len], 0);
+        assert!(full[..len].starts_with(b"martos "));
+
+        let mut short = [0xffu8; 8];
+        assert_eq!(write_version_string(&mut short), len);
+        assert_eq!(short[7], 0);
+        assert_eq!(&short[..7], &full[..7]);
+
+        let mut one = [0xffu8; 1];
+        assert_eq!(write_version_string(&mut one), len);
+        assert_eq!(one[0], 0);
+
+        assert_eq!(write_version_string(&mut []), len);
+    }
+}